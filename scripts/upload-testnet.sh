@@ -24,13 +24,17 @@ OUTPUT=$(junod tx wasm instantiate $CODE_ID "$CONTRACT_INIT_MSG" --from $KEY_NAM
 CONTRACT_ADDRESS=$(echo $OUTPUT | jq -r '.logs[0].events[0].attributes[] | select(.key == "_contract_address").value')
 echo "CONTRACT ADDRESS: " $CONTRACT_ADDRESS
 
+# Or migrate an existing instance to the new code. Instances on 0.1.0 need an owner and a bid denom.
+
+# junod tx wasm migrate $CONTRACT_ADDRESS $CODE_ID '{"owner": "'$ADMIN_ADDRESS'", "bid_denom": {"native": "'$DENOM'"}}' --from $KEY_NAME --node "https://rpc.uni.junonetwork.io:443" $TXFLAG
+
 # Start auction
 
-junod tx wasm execute $CONTRACT_ADDRESS '{"execute_start_auction": {"name": "test auction", "max_num_participants": "10"}}' --from $KEY_NAME --node "https://rpc.uni.junonetwork.io:443" $TXFLAG
+junod tx wasm execute $CONTRACT_ADDRESS '{"execute_start_auction": {"name": "test auction", "max_num_participants": "10", "bid_denom": {"native": "'$DENOM'"}}}' --from $KEY_NAME --node "https://rpc.uni.junonetwork.io:443" $TXFLAG
 
 # Bid in auction
 
-junod tx wasm execute $CONTRACT_ADDRESS '{"execute_bid": {"bid_amount": "10"}}' --from $KEY_NAME --node "https://rpc.uni.junonetwork.io:443" $TXFLAG
+junod tx wasm execute $CONTRACT_ADDRESS '{"execute_bid": {"auction_id": "1", "bid_amount": "10"}}' --amount 10$DENOM --from $KEY_NAME --node "https://rpc.uni.junonetwork.io:443" $TXFLAG
 
 # Query auction info
 
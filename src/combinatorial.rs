@@ -0,0 +1,121 @@
+//! Winner determination for combinatorial auctions.
+//!
+//! Each bidder places XOR bids on bundles of items, so wins at most one of
+//! the bundles they bid on. The items are allocated to maximize the total
+//! value of the winning bids, and every winner pays their Clarke pivot
+//! payment: the value the others could have had without them, minus the
+//! value the others get with them.
+
+use cosmwasm_std::Uint128;
+
+/// The most items a combinatorial auction may sell. Winner determination is
+/// exponential in the number of items, so this keeps it within gas limits.
+pub const MAX_ITEMS: usize = 8;
+
+/// The most bundles a single bidder may bid on.
+pub const MAX_BUNDLES_PER_BID: usize = 8;
+
+/// The most bidders a combinatorial auction may take.
+pub const MAX_BIDDERS: u64 = 32;
+
+/// A set of items, where bit `i` is set if the bundle contains item `i`.
+pub type Bundle = u16;
+
+/// One winning bid in the allocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Award {
+    /// The index of the winning bidder.
+    pub bidder: usize,
+    /// The index of the bundle they won among the bundles they bid on.
+    pub bundle: usize,
+    pub payment: Uint128,
+}
+
+/// Returns whether `items` names distinct items out of `num_items`.
+pub fn is_valid_bundle(items: &[u32], num_items: usize) -> bool {
+    !items.is_empty()
+        && items.iter().all(|item| (*item as usize) < num_items)
+        && items
+            .iter()
+            .enumerate()
+            .all(|(i, item)| !items[..i].contains(item))
+}
+
+/// Returns the set of `items`, which must be a valid bundle.
+pub fn bundle_of(items: &[u32]) -> Bundle {
+    items.iter().fold(0, |bundle, item| bundle | 1 << item)
+}
+
+// O(bidders * bundles * 2^items) per allocation, computed once for the
+// auction and once more without each winner.
+/// Allocates `num_items` items among `bids`, where `bids[i]` holds the bundles
+/// bidder `i` bid on and what they bid for them, and prices each winner with
+/// their Clarke pivot payment. Ties are broken in favour of earlier bidders.
+pub fn vcg_outcome(bids: &[Vec<(Bundle, Uint128)>], num_items: usize) -> Vec<Award> {
+    let (welfare, allocation) = best_allocation(bids, num_items, None);
+
+    allocation
+        .iter()
+        .enumerate()
+        .filter_map(|(bidder, bundle)| bundle.map(|bundle| (bidder, bundle)))
+        .map(|(bidder, bundle)| {
+            let value = bids[bidder][bundle].1.u128();
+            let (welfare_without, _) = best_allocation(bids, num_items, Some(bidder));
+            // Dropping the winner can only free items for the others, so this
+            // is never negative and never more than what the winner bid.
+            let payment = welfare_without - (welfare - value);
+            Award {
+                bidder,
+                bundle,
+                payment: Uint128::new(payment),
+            }
+        })
+        .collect()
+}
+
+/// Returns the highest total value over allocations of at most one bundle per
+/// bidder with no item sold twice, and the bundle each bidder wins in it.
+fn best_allocation(
+    bids: &[Vec<(Bundle, Uint128)>],
+    num_items: usize,
+    excluded: Option<usize>,
+) -> (u128, Vec<Option<usize>>) {
+    let num_sets = 1usize << num_items;
+
+    // best[i][sold] is the most value bidders i.. can add once the items in
+    // `sold` are gone.
+    let mut best = vec![vec![0u128; num_sets]; bids.len() + 1];
+    for bidder in (0..bids.len()).rev() {
+        for sold in 0..num_sets {
+            let mut value = best[bidder + 1][sold];
+            if excluded != Some(bidder) {
+                for (bundle, amount) in bids[bidder].iter() {
+                    let bundle = *bundle as usize;
+                    if bundle & sold == 0 {
+                        value = value.max(amount.u128() + best[bidder + 1][sold | bundle]);
+                    }
+                }
+            }
+            best[bidder][sold] = value;
+        }
+    }
+
+    let mut allocation = vec![None; bids.len()];
+    let mut sold = 0usize;
+    for bidder in 0..bids.len() {
+        if excluded == Some(bidder) {
+            continue;
+        }
+        let target = best[bidder][sold];
+        let won = bids[bidder].iter().position(|(bundle, amount)| {
+            let bundle = *bundle as usize;
+            bundle & sold == 0 && amount.u128() + best[bidder + 1][sold | bundle] == target
+        });
+        if let Some(index) = won {
+            allocation[bidder] = Some(index);
+            sold |= bids[bidder][index].0 as usize;
+        }
+    }
+
+    (best[0][0], allocation)
+}
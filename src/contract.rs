@@ -1,36 +1,117 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
-    Storage, Uint128, Uint64,
+    from_json, to_json_binary, Addr, Binary, BlockInfo, Deps, DepsMut, Env, HexBinary, MessageInfo,
+    Order, Response, StdError, StdResult, Storage, Uint128, Uint64,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
+use cw_storage_plus::{Bound, PrimaryKey};
+use cw_utils::{Expiration, Scheduled};
 
+use crate::combinatorial::{is_valid_bundle, MAX_BIDDERS, MAX_BUNDLES_PER_BID, MAX_ITEMS};
 use crate::error::ContractError;
+use crate::events;
+use crate::helpers::{
+    bid_commitment, lot_transfer_msg, lottery_commitment, reserve_commitment, transfer_msg, Payment,
+};
+use crate::migrations::migrate_auctions_from_v0_1;
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, QueryAuctionWinnerResponse, QueryBidsForBidderResponse,
-    QueryCurrentAuctionIdResponse, QueryMsg,
+    BidCursor, BidderBond, BondTerms, ExecuteMsg, InstantiateMsg, MigrateMsg, ProceedsShare,
+    ProtocolFee, QueryAllowedBiddersResponse, QueryAuctionStatusResponse,
+    QueryAuctionWinnerResponse, QueryAuctionWinnersResponse, QueryBidsForBidderResponse,
+    QueryBondsResponse, QueryCurrentAuctionIdResponse, QueryFeeConfigResponse, QueryMsg,
+    QueryOpenAuctionsResponse, QueryOwnerResponse, QueryTrustedContractsResponse, ReceiveMsg,
+    ReceiveNftMsg, ReservePrice, StartAuctionMsg, TieBreakPolicy,
+};
+use crate::state::{
+    bids, Auction, AuctionDirection, AuctionPhase, Beneficiary, Bid, BidBond, Bond, BondStatus,
+    BundleBid, BundleValue, Commitment, FeeConfig, InsufficientBidsPolicy, Lot, Reserve, TieBreak,
+    ALLOWED_BIDDERS, AUCTIONS, BONDS, COMMITMENTS, CURRENT_AUCTION_ID, FEE_CONFIG, OPEN_AUCTIONS,
+    OWNER, PENDING_OWNER, TRUSTED_CONTRACTS,
 };
-use crate::state::{Auction, Bid, Winner, AUCTIONS, BIDDERS_TO_BIDS, CURRENT_AUCTION_ID};
 
 // version info for migration
 const CONTRACT_NAME: &str = "crates.io:vcg-auction";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// pagination limits for list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// the most beneficiaries an auction's proceeds may be split between
+const MAX_BENEFICIARIES: usize = 10;
+
+// the highest protocol fee, the whole clearing payment
+const MAX_FEE_BPS: u16 = 10_000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    OWNER.save(deps.storage, &owner)?;
+
+    if let Some(fee) = msg.fee {
+        let fee = validate_fee(deps.as_ref(), fee)?;
+        FEE_CONFIG.save(deps.storage, &fee)?;
+    }
+
     CURRENT_AUCTION_ID.save(deps.storage, &0)?;
 
     Ok(Response::default())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract {
+            expected: CONTRACT_NAME.to_string(),
+            found: stored.contract,
+        });
+    }
+
+    let stored_version: semver::Version = stored.version.parse()?;
+    let new_version: semver::Version = CONTRACT_VERSION.parse()?;
+    if stored_version > new_version {
+        return Err(ContractError::MigrationDowngrade {
+            stored: stored.version,
+            new: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    // 0.1.0 kept bids on the auction and had no owner or bid denom
+    if stored_version < semver::Version::new(0, 2, 0) {
+        let (owner, bid_denom) = match (msg.owner, msg.bid_denom) {
+            (Some(owner), Some(bid_denom)) => (
+                deps.api.addr_validate(&owner)?,
+                bid_denom.into_checked(deps.as_ref())?,
+            ),
+            _ => {
+                return Err(ContractError::MissingLegacyConfig {
+                    version: stored.version,
+                })
+            }
+        };
+        OWNER.save(deps.storage, &owner)?;
+        migrate_auctions_from_v0_1(deps.storage, &owner, &bid_denom)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_event(events::contract_migrated(&stored.version, CONTRACT_VERSION)))
+}
+
 // Handle all execute messages
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
@@ -40,504 +121,6336 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ExecuteStartAuction {
-            name,
-            max_num_participants: number_of_participants,
-        } => execute_start_auction(deps, name, number_of_participants),
-        ExecuteMsg::ExecuteBid { bid_amount } => execute_bid(deps, env, info, bid_amount),
-        ExecuteMsg::ExecuteCloseAuction {} => execute_close_auction(deps),
+        ExecuteMsg::ExecuteStartAuction(msg) => execute_start_auction(
+            deps,
+            env,
+            info.sender,
+            msg,
+            Payment::Native(info.funds),
+            None,
+        ),
+        ExecuteMsg::ExecuteBid {
+            auction_id,
+            bid_amount,
+        } => execute_bid(
+            deps,
+            env,
+            auction_id.u64(),
+            info.sender,
+            bid_amount,
+            Payment::Native(info.funds),
+        ),
+        ExecuteMsg::ExecuteUpdateBid {
+            auction_id,
+            new_amount,
+        } => execute_update_bid(
+            deps,
+            env,
+            auction_id.u64(),
+            info.sender,
+            new_amount,
+            Payment::Native(info.funds),
+        ),
+        ExecuteMsg::ExecuteCommitBid {
+            auction_id,
+            commitment,
+        } => execute_commit_bid(
+            deps,
+            env,
+            auction_id.u64(),
+            info.sender,
+            commitment,
+            Payment::Native(info.funds),
+        ),
+        ExecuteMsg::ExecuteBundleBid {
+            auction_id,
+            bundles,
+        } => execute_bundle_bid(
+            deps,
+            env,
+            auction_id.u64(),
+            info.sender,
+            bundles,
+            Payment::Native(info.funds),
+        ),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::ExecuteStartReveal { auction_id } => {
+            execute_start_reveal(deps, env, info, auction_id.u64())
+        }
+        ExecuteMsg::ExecuteRevealBid {
+            auction_id,
+            amount,
+            salt,
+        } => execute_reveal_bid(deps, env, info, auction_id.u64(), amount, salt),
+        ExecuteMsg::ExecuteRevealReserve {
+            auction_id,
+            amount,
+            salt,
+        } => execute_reveal_reserve(deps, info, auction_id.u64(), amount, salt),
+        ExecuteMsg::ExecuteWithdrawBid { auction_id } => {
+            execute_withdraw_bid(deps, env, info, auction_id.u64())
+        }
+        ExecuteMsg::ExecuteRevealLotterySeed { auction_id, seed } => {
+            execute_reveal_lottery_seed(deps, info, auction_id.u64(), seed)
+        }
+        ExecuteMsg::ExecuteCloseAuction { auction_id } => {
+            execute_close_auction(deps, env, info, auction_id.u64())
+        }
+        ExecuteMsg::ExecuteSettleAuction { auction_id } => {
+            execute_settle_auction(deps, info, auction_id.u64())
+        }
+        ExecuteMsg::ExecuteDeclareDefault { auction_id, bidder } => {
+            execute_declare_default(deps, info, auction_id.u64(), bidder)
+        }
+        ExecuteMsg::ExecuteCancelAuction { auction_id, reason } => {
+            execute_cancel_auction(deps, info, auction_id.u64(), reason)
+        }
+        ExecuteMsg::ExecuteUpdateAllowedBidders {
+            auction_id,
+            add,
+            remove,
+        } => execute_update_allowed_bidders(deps, info, auction_id.u64(), add, remove),
+        ExecuteMsg::ExecuteUpdateTrustedContracts { add, remove } => {
+            execute_update_trusted_contracts(deps, info, add, remove)
+        }
+        ExecuteMsg::ExecuteUpdateFeeConfig { fee } => execute_update_fee_config(deps, info, fee),
+        ExecuteMsg::ExecuteProposeNewOwner { new_owner } => {
+            execute_propose_new_owner(deps, info, new_owner)
+        }
+        ExecuteMsg::ExecuteAcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::ExecuteRenounceOwnership {} => execute_renounce_ownership(deps, info),
     }
 }
 
-fn execute_bid(
+fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    match OWNER.may_load(storage)? {
+        Some(owner) if owner == sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+/// Auctions may be managed by the contract owner or by the auction's own auctioneer.
+fn assert_can_manage(
+    storage: &dyn Storage,
+    auction: &Auction,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if auction.auctioneer.as_ref() == Some(sender) {
+        return Ok(());
+    }
+    assert_owner(storage, sender)
+}
+
+/// The sender named in a CW20 or CW721 receive hook is only as trustworthy as the
+/// contract calling the hook, so only trusted contracts may start auctions with one.
+fn assert_trusted_contract(storage: &dyn Storage, contract: &Addr) -> Result<(), ContractError> {
+    if !TRUSTED_CONTRACTS.has(storage, contract) {
+        return Err(ContractError::UntrustedContract {});
+    }
+    Ok(())
+}
+
+fn load_auction(storage: &dyn Storage, auction_id: u64) -> Result<Auction, ContractError> {
+    AUCTIONS
+        .load(storage, auction_id)
+        .map_err(|_| ContractError::AuctionNotFound { auction_id })
+}
+
+/// Handles bids and reverse auction budgets paid in CW20 tokens, sent to this
+/// contract with `Cw20ExecuteMsg::Send`. Bids are only accepted from the token
+/// the auction names, while starting an auction takes a trusted token contract.
+fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let payment = Payment::Cw20 {
+        token: info.sender.clone(),
+        amount: wrapper.amount,
+    };
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::ExecuteStartAuction(msg) => {
+            assert_trusted_contract(deps.storage, &info.sender)?;
+            execute_start_auction(deps, env, sender, msg, payment, None)
+        }
+        ReceiveMsg::ExecuteBid {
+            auction_id,
+            bid_amount,
+        } => execute_bid(deps, env, auction_id.u64(), sender, bid_amount, payment),
+        ReceiveMsg::ExecuteUpdateBid {
+            auction_id,
+            new_amount,
+        } => execute_update_bid(deps, env, auction_id.u64(), sender, new_amount, payment),
+        ReceiveMsg::ExecuteCommitBid {
+            auction_id,
+            commitment,
+        } => execute_commit_bid(deps, env, auction_id.u64(), sender, commitment, payment),
+        ReceiveMsg::ExecuteBundleBid {
+            auction_id,
+            bundles,
+        } => execute_bundle_bid(deps, env, auction_id.u64(), sender, bundles, payment),
+    }
+}
+
+fn execute_receive_nft(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    assert_trusted_contract(deps.storage, &info.sender)?;
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let lot = Lot::Nft {
+        contract: info.sender,
+        token_id: wrapper.token_id,
+    };
+
+    match from_json(&wrapper.msg)? {
+        ReceiveNftMsg::ExecuteStartAuction(msg) => execute_start_auction(
+            deps,
+            env,
+            sender,
+            msg,
+            Payment::Native(info.funds),
+            Some(lot),
+        ),
+    }
+}
+
+fn execute_bid(
+    deps: DepsMut,
+    env: Env,
+    auction_id: u64,
+    bidder: Addr,
     bid_amount: Uint128,
+    payment: Payment,
 ) -> Result<Response, ContractError> {
-    let current_auction_id = CURRENT_AUCTION_ID.load(deps.storage)?;
-    let mut auction: Auction = AUCTIONS
-        .load(deps.storage, current_auction_id)
-        .map_err(|_| ContractError::AuctionNotFound {
-            auction_id: current_auction_id,
-        })?;
-
-    if !auction.in_progress {
-        return Err(ContractError::AuctionNotInProgress {});
+    let mut auction = load_auction(deps.storage, auction_id)?;
+
+    assert_bidding_open(&auction, &env.block)?;
+
+    if auction.sealed {
+        return Err(ContractError::AuctionIsSealed {});
+    }
+
+    if auction.is_combinatorial() {
+        return Err(ContractError::IsCombinatorial {});
     }
 
-    if BIDDERS_TO_BIDS
-        .may_load(deps.storage, (&info.sender, current_auction_id))?
+    assert_bidder_allowed(deps.storage, &auction, &bidder)?;
+
+    if bids()
+        .may_load(deps.storage, (auction_id, &bidder))?
         .is_some()
     {
         return Err(ContractError::BidAlreadyPlaced {});
     }
 
+    if bid_amount.is_zero() {
+        return Err(ContractError::BidAmountTooLow {});
+    }
+
+    let deposit = take_bid_funds(deps.storage, &auction, &bidder, &payment)?;
+    match auction.direction {
+        // The full bid is held in escrow until the auction closes
+        AuctionDirection::Forward if deposit != bid_amount => {
+            return Err(ContractError::BidFundsMismatch {
+                bid_amount,
+                deposit,
+            });
+        }
+        AuctionDirection::Forward => {}
+        AuctionDirection::Reverse => assert_within_budget(&auction, bid_amount)?,
+    }
+
     let bid = Bid {
-        auction_id: current_auction_id,
+        auction_id,
         amount: bid_amount,
-        bidder: info.sender.clone(),
+        bidder: bidder.clone(),
         timestamp: env.block.time,
+        sequence: auction.take_sequence(),
+        deposit,
+        amendments: 0,
+        last_updated: env.block.time,
     };
 
-    auction.add_bid(deps.storage, bid.clone())?;
-    BIDDERS_TO_BIDS.save(deps.storage, (&info.sender, auction.id), &bid)?;
+    auction.add_bid(deps.storage, bid)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_event(events::bid_placed(&auction, &bidder, Some(bid_amount))))
 }
 
-fn execute_start_auction(
+fn execute_update_bid(
     deps: DepsMut,
-    name: String,
-    max_participants: Uint64,
+    env: Env,
+    auction_id: u64,
+    bidder: Addr,
+    new_amount: Uint128,
+    payment: Payment,
 ) -> Result<Response, ContractError> {
-    let auction_id = get_and_increment_auction_id(deps.storage)?;
+    let mut auction = load_auction(deps.storage, auction_id)?;
 
-    // Check if auction already exists
-    if AUCTIONS.may_load(deps.storage, auction_id)?.is_some() {
-        return Err(ContractError::AuctionAlreadyInProgress {});
+    assert_bidding_open(&auction, &env.block)?;
+
+    if auction.sealed {
+        return Err(ContractError::AuctionIsSealed {});
     }
 
-    // Create a new auction
-    let auction = Auction {
-        id: auction_id,
-        in_progress: true,
-        sorted_bids: vec![],
-        name: name,
-        max_participants,
-        winner: None,
-    };
+    if auction.is_combinatorial() {
+        return Err(ContractError::IsCombinatorial {});
+    }
 
-    AUCTIONS.save(deps.storage, auction_id, &auction)?;
+    if !auction.allow_amendments {
+        return Err(ContractError::AmendmentsDisabled {});
+    }
 
-    Ok(Response::default())
-}
+    assert_bidder_allowed(deps.storage, &auction, &bidder)?;
 
-fn execute_close_auction(deps: DepsMut) -> Result<Response, ContractError> {
-    let auction_id = CURRENT_AUCTION_ID.load(deps.storage)?;
-    AUCTIONS.update(
-        deps.storage,
-        auction_id,
-        |auction: Option<Auction>| match auction {
-            Some(mut auction) => {
-                if !auction.in_progress {
-                    return Err(ContractError::AuctionNotInProgress {});
-                }
-                auction.in_progress = false;
-                Ok(auction)
-            }
-            None => Err(ContractError::AuctionNotFound { auction_id }),
-        },
-    )?;
+    if new_amount.is_zero() {
+        return Err(ContractError::BidAmountTooLow {});
+    }
 
-    Ok(Response::default())
-}
+    let mut bid = bids()
+        .may_load(deps.storage, (auction.id, &bidder))?
+        .ok_or(ContractError::BidNotFound {})?;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::QueryGetAuctionWinner { auction_id } => {
-            return query_get_winner(deps, auction_id.u64())
+    // Making a bid worse backs out of it like a withdrawal does, lowering a
+    // forward bid or raising a reverse ask
+    if auction.direction.prefers(bid.amount, new_amount)
+        && auction
+            .withdrawals_locked_after
+            .as_ref()
+            .is_some_and(|cutoff| cutoff.is_expired(&env.block))
+    {
+        return Err(ContractError::WithdrawalsLocked {});
+    }
+
+    let mut refund = Uint128::zero();
+    match auction.direction {
+        AuctionDirection::Forward if new_amount > bid.amount => {
+            let bid_amount = new_amount - bid.amount;
+            let deposit = payment.amount_in(&auction.bid_denom)?;
+            if deposit != bid_amount {
+                return Err(ContractError::BidFundsMismatch {
+                    bid_amount,
+                    deposit,
+                });
+            }
+            bid.deposit += deposit;
+        }
+        AuctionDirection::Forward => {
+            payment.nonpayable()?;
+            refund = bid.amount - new_amount;
+            bid.deposit -= refund;
+        }
+        AuctionDirection::Reverse => {
+            payment.nonpayable()?;
+            assert_within_budget(&auction, new_amount)?;
         }
-        QueryMsg::QueryGetBidsForBidder {
-            bidder,
-            start_after,
-            limit,
-        } => query_get_bids_for_bidder(deps, bidder, start_after, limit),
-        QueryMsg::QueryGetBidsForAuction {
-            auction_id,
-            start_after,
-            limit,
-        } => query_get_bids_for_auction(deps, auction_id.u64(), start_after, limit),
-        QueryMsg::QueryGetCurrentAuctionId {} => query_get_current_auction_id(deps),
     }
-}
 
-// TODO: paginate query
-fn query_get_bids_for_bidder(
-    deps: Deps,
-    bidder: String,
-    _start_after: Option<Uint128>,
-    _limit: Option<u32>,
-) -> StdResult<Binary> {
-    let bidder = deps.api.addr_validate(&bidder)?;
-    let bids = BIDDERS_TO_BIDS
-        .prefix(&bidder)
-        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .map(|pair| Ok(pair?.1))
-        .collect::<StdResult<Vec<_>>>()?;
+    bid.amount = new_amount;
+    bid.sequence = auction.take_sequence();
+    bid.amendments += 1;
+    bid.last_updated = env.block.time;
+    bids().save(deps.storage, (auction.id, &bidder), &bid)?;
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    let mut response = Response::new().add_event(events::bid_updated(&bid));
+    if !refund.is_zero() {
+        response = response.add_message(transfer_msg(&auction.bid_denom, &bidder, refund)?);
+    }
 
-    return Ok(to_binary(&QueryBidsForBidderResponse { bids })?);
+    Ok(response)
 }
 
-// TODO: paginate query
-fn query_get_bids_for_auction(
-    deps: Deps,
+fn execute_bundle_bid(
+    deps: DepsMut,
+    env: Env,
     auction_id: u64,
-    _start_after: Option<Uint128>,
-    _limit: Option<u32>,
-) -> StdResult<Binary> {
-    let auction: Auction = AUCTIONS
-        .load(deps.storage, auction_id)
-        .map_err(|_| StdError::generic_err(format!("auction with id {} not found", auction_id)))?;
-
-    return Ok(to_binary(&auction.sorted_bids)?);
-}
+    bidder: Addr,
+    bundles: Vec<BundleValue>,
+    payment: Payment,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
 
-fn query_get_winner(deps: Deps, auction_id: u64) -> StdResult<Binary> {
-    let auction: Auction = AUCTIONS
-        .load(deps.storage, auction_id)
-        .map_err(|_| StdError::generic_err("auction not found"))?;
+    assert_bidding_open(&auction, &env.block)?;
 
-    if auction.is_in_progress() {
-        return Err(StdError::generic_err("Auction in progress"));
+    if !auction.is_combinatorial() {
+        return Err(ContractError::NotCombinatorial {});
     }
 
-    match auction.winner {
-        Some(winner) => {
-            return Ok(to_binary(&winner)?);
-        }
-        None => {
-            let highest_bid = auction.get_highest_bid().ok_or(StdError::generic_err(
-                "Auction has no bids, cannot determine winner",
-            ))?;
-            let second_highest_bid =
-                auction
-                    .get_second_highest_bid()
-                    .ok_or(StdError::generic_err(
-                        "Auction has only one bid, cannot determine winner",
-                    ))?;
-            let winner = Winner {
-                bidder: highest_bid.bidder.clone(),
-                auction_id,
-                amount_owed: second_highest_bid.amount,
-            };
-            return Ok(to_binary(&QueryAuctionWinnerResponse { winner: winner })?);
-        }
-    }
-}
+    assert_bidder_allowed(deps.storage, &auction, &bidder)?;
 
-fn query_get_current_auction_id(deps: Deps) -> StdResult<Binary> {
-    let auction_id = CURRENT_AUCTION_ID.load(deps.storage)?;
-    return Ok(to_binary(&QueryCurrentAuctionIdResponse {
-        auction_id: auction_id.into(),
-    })?);
-}
+    if auction.bundle_bids.iter().any(|bid| bid.bidder == bidder) {
+        return Err(ContractError::BidAlreadyPlaced {});
+    }
 
-pub fn get_and_increment_auction_id(storage: &mut dyn Storage) -> StdResult<u64> {
-    let new_id = CURRENT_AUCTION_ID.update(storage, |id| -> StdResult<_> {
-        let new_id = id + 1;
-        Ok(new_id)
-    })?;
-    Ok(new_id)
-}
+    if auction.bundle_bids.len() as u64 >= auction.max_participants.u64() {
+        return Err(ContractError::MaxParticipantsReached {
+            max_participants: auction.max_participants,
+        });
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::error::Error;
-    use std::fmt::format;
+    if bundles.len() > MAX_BUNDLES_PER_BID {
+        return Err(ContractError::TooManyBundles {
+            max: MAX_BUNDLES_PER_BID,
+        });
+    }
 
-    use crate::msg::{ExecuteMsg, QueryBidsForBidderResponse};
-    use crate::state::{Bid, Winner};
-    use cosmwasm_std::{Addr, Empty, StdError, Timestamp, Uint128, Uint64};
-    use cw_multi_test::{App, Contract, ContractWrapper};
-    use cw_multi_test::{AppResponse, Executor};
-    use rand::Rng;
+    if bundles.is_empty() || bundles.iter().any(|bundle| bundle.amount.is_zero()) {
+        return Err(ContractError::BidAmountTooLow {});
+    }
 
-    fn auction_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(super::execute, super::instantiate, super::query);
-        Box::new(contract)
+    if bundles
+        .iter()
+        .any(|bundle| !is_valid_bundle(&bundle.items, auction.items.len()))
+    {
+        return Err(ContractError::InvalidBundle {});
     }
 
-    fn instantiate_auction(app: &mut App) -> Addr {
-        let code_id: u64 = app.store_code(auction_contract());
-        let auction = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked(ADMIN),
-                &crate::msg::InstantiateMsg {},
-                &[],
-                "coin",
-                None,
-            )
-            .unwrap();
-        return auction;
+    // The bidder wins at most one bundle, so escrowing their highest offer covers any outcome
+    let bid_amount = bundles.iter().map(|bundle| bundle.amount).max().unwrap();
+    let deposit = take_bid_funds(deps.storage, &auction, &bidder, &payment)?;
+    if deposit != bid_amount {
+        return Err(ContractError::BidFundsMismatch {
+            bid_amount,
+            deposit,
+        });
     }
 
-    // Returns current auction id
-    fn start_auction(app: &mut App, auction: Addr) -> u64 {
-        // Get current auction ID
-        let resp = app
-            .wrap()
-            .query_wasm_smart::<crate::msg::QueryCurrentAuctionIdResponse>(
-                auction.clone(),
-                &crate::msg::QueryMsg::QueryGetCurrentAuctionId {},
-            )
-            .unwrap();
+    let sequence = auction.take_sequence();
+    auction.bundle_bids.push(BundleBid {
+        auction_id: auction.id,
+        bidder: bidder.clone(),
+        bundles,
+        timestamp: env.block.time,
+        sequence,
+        deposit,
+    });
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
 
-        let prev_auction_id = resp.auction_id.u64();
+    Ok(Response::new().add_event(events::bid_placed(&auction, &bidder, Some(bid_amount))))
+}
 
-        // Start auction
-        app.execute_contract(
-            Addr::unchecked(ADMIN),
-            auction.clone(),
-            &ExecuteMsg::ExecuteStartAuction {
-                max_num_participants: Uint64::from(10000u64),
-                name: "auction_1".to_string(),
-            },
-            &[],
-        )
-        .unwrap();
+fn execute_commit_bid(
+    deps: DepsMut,
+    env: Env,
+    auction_id: u64,
+    bidder: Addr,
+    commitment: HexBinary,
+    payment: Payment,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
 
-        // Ensure current auction ID is correct
-        let resp = app
-            .wrap()
-            .query_wasm_smart::<crate::msg::QueryCurrentAuctionIdResponse>(
-                auction.clone(),
-                &crate::msg::QueryMsg::QueryGetCurrentAuctionId {},
-            )
-            .unwrap();
+    assert_bidding_open(&auction, &env.block)?;
 
-        let current_auction_id = resp.auction_id.u64();
-        assert_eq!(current_auction_id, prev_auction_id + 1);
-        return current_auction_id;
+    if !auction.sealed {
+        return Err(ContractError::AuctionNotSealed {});
     }
 
-    fn close_auction(app: &mut App, auction: Addr) {
-        app.execute_contract(
-            Addr::unchecked(ADMIN),
-            auction,
-            &ExecuteMsg::ExecuteCloseAuction {},
-            &[],
-        )
-        .unwrap();
-    }
+    assert_bidder_allowed(deps.storage, &auction, &bidder)?;
 
-    fn bid(
-        app: &mut App,
-        auction: Addr,
-        bidder: Addr,
-        bid_amount: Uint128,
-    ) -> Result<AppResponse, anyhow::Error> {
-        return app.execute_contract(bidder, auction, &ExecuteMsg::ExecuteBid { bid_amount }, &[]);
+    if COMMITMENTS.has(deps.storage, (auction.id, &bidder)) {
+        return Err(ContractError::BidAlreadyPlaced {});
     }
 
-    const ADMIN: &str = "admin";
+    // The deposit must cover the bid once it is revealed. Bidders may deposit
+    // more than they intend to bid so the deposit doesn't give the bid away.
+    let deposit = take_bid_funds(deps.storage, &auction, &bidder, &payment)?;
 
-    #[test]
-    fn test_auction_start_and_close() {
-        let mut app = App::default();
-        let auction = instantiate_auction(&mut app);
-        start_auction(&mut app, auction.clone());
-        close_auction(&mut app, auction);
-    }
+    auction.add_commitment()?;
+    let sequence = auction.take_sequence();
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+    COMMITMENTS.save(
+        deps.storage,
+        (auction.id, &bidder),
+        &Commitment {
+            auction_id: auction.id,
+            bidder: bidder.clone(),
+            commitment,
+            revealed: false,
+            timestamp: env.block.time,
+            sequence,
+            deposit,
+        },
+    )?;
+
+    // The amount stays hidden until the bid is revealed
+    Ok(Response::new().add_event(events::bid_placed(&auction, &bidder, None)))
+}
+
+fn execute_withdraw_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+
+    assert_bidding_open(&auction, &env.block)?;
+
+    if auction
+        .withdrawals_locked_after
+        .as_ref()
+        .is_some_and(|cutoff| cutoff.is_expired(&env.block))
+    {
+        return Err(ContractError::WithdrawalsLocked {});
+    }
+
+    let deposit = if auction.sealed {
+        let commitment = COMMITMENTS
+            .may_load(deps.storage, (auction.id, &info.sender))?
+            .ok_or(ContractError::NoCommitmentFound {})?;
+        COMMITMENTS.remove(deps.storage, (auction.id, &info.sender));
+        auction.num_commitments -= 1;
+        AUCTIONS.save(deps.storage, auction.id, &auction)?;
+        commitment.deposit
+    } else if auction.is_combinatorial() {
+        let index = auction
+            .bundle_bids
+            .iter()
+            .position(|bid| bid.bidder == info.sender)
+            .ok_or(ContractError::BidNotFound {})?;
+        let bid = auction.bundle_bids.remove(index);
+        AUCTIONS.save(deps.storage, auction.id, &auction)?;
+        bid.deposit
+    } else {
+        auction.remove_bid(deps.storage, &info.sender)?.deposit
+    };
+    let refund =
+        deposit + release_bond(deps.storage, auction.id, &info.sender, BondStatus::Returned)?;
+
+    let mut response = Response::new().add_event(events::bid_withdrawn(&auction, &info.sender));
+    if !refund.is_zero() {
+        response = response.add_message(transfer_msg(&auction.bid_denom, &info.sender, refund)?);
+    }
+
+    Ok(response)
+}
+
+fn execute_start_reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    assert_bidding_open(&auction, &env.block)?;
+
+    if !auction.sealed {
+        return Err(ContractError::AuctionNotSealed {});
+    }
+
+    auction.start_reveal();
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    Ok(Response::new().add_event(events::reveal_started(auction.id)))
+}
+
+fn execute_reveal_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+    amount: Uint128,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+
+    if !auction.sealed {
+        return Err(ContractError::AuctionNotSealed {});
+    }
+
+    if auction.current_phase(&env.block) != AuctionPhase::Reveal {
+        return Err(ContractError::NotInRevealPhase {});
+    }
+
+    let mut commitment = COMMITMENTS
+        .may_load(deps.storage, (auction.id, &info.sender))?
+        .ok_or(ContractError::NoCommitmentFound {})?;
+
+    if commitment.revealed {
+        return Err(ContractError::BidAlreadyRevealed {});
+    }
+
+    if bid_commitment(&info.sender, amount, &salt) != commitment.commitment {
+        return Err(ContractError::CommitmentMismatch {});
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::BidAmountTooLow {});
+    }
+
+    match auction.direction {
+        AuctionDirection::Forward if amount > commitment.deposit => {
+            return Err(ContractError::InsufficientDeposit {
+                bid_amount: amount,
+                deposit: commitment.deposit,
+            });
+        }
+        AuctionDirection::Reverse => assert_within_budget(&auction, amount)?,
+        _ => {}
+    }
+
+    commitment.revealed = true;
+    COMMITMENTS.save(deps.storage, (auction.id, &info.sender), &commitment)?;
+
+    // The bid keeps the time it was committed at, not the time it was revealed.
+    let bid = Bid {
+        auction_id: auction.id,
+        amount,
+        bidder: info.sender.clone(),
+        timestamp: commitment.timestamp,
+        sequence: commitment.sequence,
+        deposit: commitment.deposit,
+        amendments: 0,
+        last_updated: commitment.timestamp,
+    };
+
+    let event = events::bid_revealed(&auction, &bid);
+    auction.add_bid(deps.storage, bid)?;
+
+    Ok(Response::new().add_event(event))
+}
+
+fn execute_reveal_reserve(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    amount: Uint128,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    if !auction.is_in_progress() {
+        return Err(ContractError::AuctionNotInProgress {});
+    }
+
+    match &mut auction.reserve {
+        Some(Reserve::Secret {
+            commitment,
+            amount: revealed,
+        }) => {
+            if revealed.is_some() {
+                return Err(ContractError::ReserveAlreadyRevealed {});
+            }
+            if reserve_commitment(amount, &salt) != *commitment {
+                return Err(ContractError::ReserveMismatch {});
+            }
+            *revealed = Some(amount);
+        }
+        _ => return Err(ContractError::NoSecretReserve {}),
+    }
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    Ok(Response::new().add_event(events::reserve_revealed(auction.id, amount)))
+}
+
+fn execute_reveal_lottery_seed(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    seed: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    if !auction.is_in_progress() {
+        return Err(ContractError::AuctionNotInProgress {});
+    }
+
+    match &mut auction.tie_break {
+        TieBreak::Lottery {
+            commitment,
+            seed: revealed,
+        } => {
+            if revealed.is_some() {
+                return Err(ContractError::LotterySeedAlreadyRevealed {});
+            }
+            if lottery_commitment(&seed) != *commitment {
+                return Err(ContractError::LotterySeedMismatch {});
+            }
+            *revealed = Some(seed.clone());
+        }
+        _ => return Err(ContractError::NoLottery {}),
+    }
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    Ok(Response::new().add_event(events::lottery_seed_revealed(auction.id, &seed)))
+}
+
+fn execute_start_auction(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    msg: StartAuctionMsg,
+    payment: Payment,
+    mut lot: Option<Lot>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &sender)?;
+
+    let direction = msg.direction.unwrap_or(AuctionDirection::Forward);
+    let sealed = msg.sealed.unwrap_or(false);
+    let quantity = msg.quantity.map_or(1, |quantity| quantity.u64());
+    if quantity == 0 || quantity >= msg.max_num_participants.u64() {
+        return Err(ContractError::InvalidQuantity {});
+    }
+
+    let items = msg.items.unwrap_or_default();
+    if !items.is_empty()
+        && (sealed
+            || direction == AuctionDirection::Reverse
+            || msg.reserve.is_some()
+            || quantity != 1
+            || items.len() > MAX_ITEMS
+            || msg.max_num_participants.u64() > MAX_BIDDERS)
+    {
+        return Err(ContractError::InvalidCombinatorialAuction {});
+    }
+
+    validate_schedule(
+        &env.block,
+        msg.start.as_ref(),
+        msg.end.as_ref(),
+        msg.reveal_end.as_ref(),
+        sealed,
+    )?;
+
+    let bid_denom = msg.bid_denom.into_checked(deps.as_ref())?;
+
+    let beneficiaries = match (&direction, msg.beneficiaries) {
+        (AuctionDirection::Forward, None) => vec![Beneficiary {
+            address: sender.clone(),
+            weight: 1,
+        }],
+        (AuctionDirection::Forward, Some(shares)) => validate_beneficiaries(deps.as_ref(), shares)?,
+        (AuctionDirection::Reverse, None) => vec![],
+        (AuctionDirection::Reverse, Some(_)) => {
+            return Err(ContractError::BeneficiariesNotAllowed {})
+        }
+    };
+
+    let bond = msg
+        .bond
+        .map(|terms| validate_bond(deps.as_ref(), terms))
+        .transpose()?;
+
+    // The buyer in a reverse auction escrows the budget winners are paid from,
+    // while funds sent to start a forward auction are the lot it sells
+    let budget = match direction {
+        AuctionDirection::Forward if lot.is_none() => {
+            lot = payment
+                .single_asset()?
+                .map(|(denom, amount)| Lot::Tokens { denom, amount });
+            Uint128::zero()
+        }
+        AuctionDirection::Forward => {
+            payment.nonpayable()?;
+            Uint128::zero()
+        }
+        AuctionDirection::Reverse => payment.amount_in(&bid_denom)?,
+    };
+
+    // A lot is sold whole, to a single winner who pays for it
+    if lot.is_some()
+        && (direction == AuctionDirection::Reverse || quantity != 1 || !items.is_empty())
+    {
+        return Err(ContractError::InvalidLot {});
+    }
+
+    let auctioneer = msg
+        .auctioneer
+        .map(|auctioneer| deps.api.addr_validate(&auctioneer))
+        .transpose()?;
+    let auction_id = get_and_increment_auction_id(deps.storage)?;
+
+    // Check if auction already exists
+    if AUCTIONS.may_load(deps.storage, auction_id)?.is_some() {
+        return Err(ContractError::AuctionAlreadyInProgress {});
+    }
+
+    // Create a new auction
+    let auction = Auction {
+        id: auction_id,
+        phase: AuctionPhase::Bidding,
+        direction,
+        budget,
+        sealed,
+        bundle_bids: vec![],
+        name: msg.name,
+        max_participants: msg.max_num_participants,
+        num_bids: 0,
+        num_commitments: 0,
+        next_sequence: 0,
+        tie_break: match msg.tie_break.unwrap_or(TieBreakPolicy::LatestBid) {
+            TieBreakPolicy::EarliestBid => TieBreak::EarliestBid,
+            TieBreakPolicy::LatestBid => TieBreak::LatestBid,
+            TieBreakPolicy::LowestAddress => TieBreak::LowestAddress,
+            TieBreakPolicy::Lottery(commitment) => TieBreak::Lottery {
+                commitment,
+                seed: None,
+            },
+        },
+        quantity,
+        items,
+        bid_denom,
+        creator: sender,
+        auctioneer,
+        restrict_bidders: msg.allowed_bidders.is_some(),
+        insufficient_bids_policy: msg
+            .on_insufficient_bids
+            .unwrap_or(InsufficientBidsPolicy::Reject),
+        reserve: msg.reserve.map(|reserve| match reserve {
+            ReservePrice::Public(amount) => Reserve::Public(amount),
+            ReservePrice::Secret(commitment) => Reserve::Secret {
+                commitment,
+                amount: None,
+            },
+        }),
+        reserve_binding: false,
+        bidding_start: msg.start,
+        bidding_end: msg.end,
+        reveal_end: msg.reveal_end,
+        withdrawals_locked_after: msg.withdrawals_locked_after,
+        allow_amendments: msg.allow_amendments.unwrap_or(true),
+        winners: vec![],
+        cancel_reason: None,
+        beneficiaries,
+        fee: FEE_CONFIG.may_load(deps.storage)?,
+        protocol_fee: Uint128::zero(),
+        lot,
+        bond,
+    };
+
+    AUCTIONS.save(deps.storage, auction_id, &auction)?;
+    OPEN_AUCTIONS.save(deps.storage, auction_id, &())?;
+
+    for bidder in msg.allowed_bidders.unwrap_or_default() {
+        let bidder = deps.api.addr_validate(&bidder)?;
+        ALLOWED_BIDDERS.save(deps.storage, (auction_id, &bidder), &())?;
+    }
+
+    Ok(Response::new().add_event(events::auction_started(&auction)))
+}
+
+fn execute_close_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    let phase = auction.current_phase(&env.block);
+
+    match phase {
+        AuctionPhase::Closed | AuctionPhase::Settled => {
+            return Err(ContractError::AuctionNotInProgress {})
+        }
+        AuctionPhase::Cancelled => return Err(ContractError::AuctionCancelled {}),
+        _ => {}
+    }
+
+    // Once the auction's schedule has run out anyone may close it
+    if phase != AuctionPhase::Ended {
+        assert_can_manage(deps.storage, &auction, &info.sender)?;
+    }
+
+    if auction.sealed && phase != AuctionPhase::Reveal && phase != AuctionPhase::Ended {
+        return Err(ContractError::NotInRevealPhase {});
+    }
+
+    // Bidders may reveal until `reveal_end`, closing before then would drop
+    // their bids, so only an auction with every bid revealed closes early
+    if phase == AuctionPhase::Reveal
+        && auction.reveal_end.is_some()
+        && has_unrevealed_commitments(deps.storage, auction.id)?
+    {
+        return Err(ContractError::RevealPhaseOpen {});
+    }
+
+    // An unrevealed lottery can't hold the auction open past its end, ties
+    // then go to the earliest bid
+    if let TieBreak::Lottery { seed: None, .. } = auction.tie_break {
+        if phase != AuctionPhase::Ended {
+            return Err(ContractError::LotterySeedNotRevealed {});
+        }
+    }
+
+    auction.winners = auction.determine_winners(deps.storage)?;
+    auction.reserve_binding = auction.is_reserve_binding(deps.storage)?;
+    auction.protocol_fee = auction
+        .winners
+        .iter()
+        .map(|winner| auction.winner_fee(winner))
+        .sum();
+    if auction.winners.is_empty()
+        && auction.reserve.is_none()
+        && auction.insufficient_bids_policy == InsufficientBidsPolicy::Reject
+    {
+        return Err(ContractError::TooFewParticipants {
+            needed: auction.min_bids(),
+        });
+    }
+
+    auction.end();
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+    OPEN_AUCTIONS.remove(deps.storage, auction.id);
+
+    let response = Response::new()
+        .add_event(events::auction_closed(&auction))
+        .add_events(auction.winners.iter().map(events::winner_determined));
+
+    // Without `reveal_end` the reveal window lasts until the auction is closed
+    let reveal_expired = phase == AuctionPhase::Ended || auction.reveal_end.is_none();
+    let mut payouts = escrow_payouts(deps.storage, &auction)?;
+    payouts.extend(release_bonds(deps.storage, &auction, reveal_expired)?);
+    let mut response = add_payouts(response, &auction, payouts)?;
+    if auction.winners.is_empty() {
+        response = add_lot_transfer(response, &auction, &auction.creator)?;
+    }
+
+    Ok(response)
+}
+
+fn execute_settle_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+
+    // Reverse auction winners are paid on settlement, so it is left to the buyer
+    // and managers, who may first declare that a winner defaulted
+    if auction.direction == AuctionDirection::Reverse && auction.creator != info.sender {
+        assert_can_manage(deps.storage, &auction, &info.sender)?;
+    }
+
+    match auction.phase {
+        AuctionPhase::Closed => {}
+        AuctionPhase::Settled => return Err(ContractError::AuctionAlreadySettled {}),
+        AuctionPhase::Cancelled => return Err(ContractError::AuctionCancelled {}),
+        _ => return Err(ContractError::AuctionNotClosed {}),
+    }
+
+    auction.settle();
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    let proceeds = total_owed(&auction)?;
+    let mut payouts = proceeds_payouts(&auction)?;
+    payouts.extend(release_bonds(deps.storage, &auction, false)?);
+
+    let response = Response::new().add_event(events::auction_settled(auction.id, proceeds));
+
+    let mut response = add_payouts(response, &auction, payouts)?;
+    if let Some(winner) = auction.winners.first() {
+        response = add_lot_transfer(response, &auction, &winner.bidder)?;
+    }
+
+    Ok(response)
+}
+
+fn execute_cancel_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    match auction.phase {
+        AuctionPhase::Closed | AuctionPhase::Settled => {
+            return Err(ContractError::AuctionNotInProgress {})
+        }
+        AuctionPhase::Cancelled => return Err(ContractError::AuctionCancelled {}),
+        _ => {}
+    }
+
+    auction.cancel(reason.clone());
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+    OPEN_AUCTIONS.remove(deps.storage, auction.id);
+
+    let response = Response::new().add_event(events::auction_cancelled(&auction, &reason));
+
+    // Without winners every bid, bond and the whole budget are refunded
+    let mut payouts = escrow_payouts(deps.storage, &auction)?;
+    payouts.extend(release_bonds(deps.storage, &auction, false)?);
+    let response = add_payouts(response, &auction, payouts)?;
+
+    Ok(add_lot_transfer(response, &auction, &auction.creator)?)
+}
+
+fn execute_declare_default(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    match auction.phase {
+        AuctionPhase::Closed => {}
+        AuctionPhase::Settled => return Err(ContractError::AuctionAlreadySettled {}),
+        AuctionPhase::Cancelled => return Err(ContractError::AuctionCancelled {}),
+        _ => return Err(ContractError::AuctionNotClosed {}),
+    }
+
+    let slash_recipient = match &auction.bond {
+        Some(bond) => bond.slash_recipient.clone(),
+        None => return Err(ContractError::NoBond {}),
+    };
+
+    // Forward auction winners paid into escrow when they bid, only sellers can fail to deliver
+    if auction.direction != AuctionDirection::Reverse {
+        return Err(ContractError::WinnerCannotDefault {});
+    }
+
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let index = auction
+        .winners
+        .iter()
+        .position(|winner| winner.bidder == bidder)
+        .ok_or(ContractError::NotAWinner {})?;
+    let winner = auction.winners.remove(index);
+    auction.protocol_fee -= auction.winner_fee(&winner);
+    AUCTIONS.save(deps.storage, auction.id, &auction)?;
+
+    let slashed = release_bond(deps.storage, auction.id, &bidder, BondStatus::Slashed)?;
+    let mut payouts = vec![
+        Payout {
+            recipient: slash_recipient,
+            amount: slashed,
+            kind: events::SETTLEMENT_SLASH,
+        },
+        Payout {
+            recipient: auction.creator.clone(),
+            amount: winner.amount_owed,
+            kind: events::SETTLEMENT_REFUND,
+        },
+    ];
+    payouts.retain(|payout| !payout.amount.is_zero());
+
+    let response = Response::new().add_event(events::winner_defaulted(&winner));
+
+    Ok(add_payouts(response, &auction, payouts)?)
+}
+
+/// Adds the transfer of the auction's escrowed lot, if it has one, to `response`.
+fn add_lot_transfer(
+    response: Response,
+    auction: &Auction,
+    recipient: &Addr,
+) -> StdResult<Response> {
+    Ok(match &auction.lot {
+        Some(lot) => response
+            .add_message(lot_transfer_msg(lot, recipient)?)
+            .add_event(events::lot_transferred(auction.id, lot, recipient)),
+        None => response,
+    })
+}
+
+/// Adds a transfer and a settlement event for each payout to `response`.
+fn add_payouts(
+    mut response: Response,
+    auction: &Auction,
+    payouts: Vec<Payout>,
+) -> StdResult<Response> {
+    for payout in payouts {
+        response = response
+            .add_message(transfer_msg(
+                &auction.bid_denom,
+                &payout.recipient,
+                payout.amount,
+            )?)
+            .add_event(events::settlement(
+                auction.id,
+                &payout.recipient,
+                payout.amount,
+                payout.kind,
+            ));
+    }
+    Ok(response)
+}
+
+/// Escrowed funds owed to `recipient` once an auction has closed or settled.
+struct Payout {
+    recipient: Addr,
+    amount: Uint128,
+    /// One of the `events::SETTLEMENT_*` kinds.
+    kind: &'static str,
+}
+
+/// Returns the funds paid with a bid that are held in escrow for the bid itself.
+/// Auctions with a bond take it out of the funds first and record it as held
+/// for the bidder.
+fn take_bid_funds(
+    storage: &mut dyn Storage,
+    auction: &Auction,
+    bidder: &Addr,
+    payment: &Payment,
+) -> Result<Uint128, ContractError> {
+    let bond = match (&auction.bond, &auction.direction) {
+        (Some(bond), _) => bond.amount,
+        (None, AuctionDirection::Forward) => return payment.amount_in(&auction.bid_denom),
+        // Asks are paid out of the buyer's budget, so sellers escrow nothing
+        (None, AuctionDirection::Reverse) => {
+            payment.nonpayable()?;
+            return Ok(Uint128::zero());
+        }
+    };
+
+    let paid = match payment {
+        Payment::Native(funds) if funds.is_empty() => Uint128::zero(),
+        _ => payment.amount_in(&auction.bid_denom)?,
+    };
+    if paid.is_zero() {
+        return Err(ContractError::MissingBond { bond });
+    }
+    if paid < bond {
+        return Err(ContractError::InsufficientBond {
+            bond,
+            deposit: paid,
+        });
+    }
+
+    let deposit = paid - bond;
+    if auction.direction == AuctionDirection::Reverse && !deposit.is_zero() {
+        return Err(ContractError::BidFundsMismatch {
+            bid_amount: Uint128::zero(),
+            deposit,
+        });
+    }
+
+    BONDS.save(
+        storage,
+        (auction.id, bidder),
+        &Bond {
+            amount: bond,
+            status: BondStatus::Held,
+        },
+    )?;
+    Ok(deposit)
+}
+
+/// Marks the bidder's bond as returned or slashed if it is still held, and
+/// returns its amount. Returns zero if they have no bond held.
+fn release_bond(
+    storage: &mut dyn Storage,
+    auction_id: u64,
+    bidder: &Addr,
+    status: BondStatus,
+) -> StdResult<Uint128> {
+    match BONDS.may_load(storage, (auction_id, bidder))? {
+        Some(mut bond) if bond.status == BondStatus::Held => {
+            bond.status = status;
+            BONDS.save(storage, (auction_id, bidder), &bond)?;
+            Ok(bond.amount)
+        }
+        _ => Ok(Uint128::zero()),
+    }
+}
+
+// O(n) in the number of commitments
+fn has_unrevealed_commitments(storage: &dyn Storage, auction_id: u64) -> StdResult<bool> {
+    for commitment in COMMITMENTS
+        .prefix(auction_id)
+        .range(storage, None, None, Order::Ascending)
+    {
+        if !commitment?.1.revealed {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns or slashes the bonds still held for the auction and returns the payouts.
+/// On close winners keep their bond until the auction settles, and the bonds of
+/// sealed bids that were never revealed are slashed if `reveal_expired`, meaning
+/// their bidders had the whole reveal window. Settling or cancelling the auction
+/// returns every bond still held.
+fn release_bonds(
+    storage: &mut dyn Storage,
+    auction: &Auction,
+    reveal_expired: bool,
+) -> StdResult<Vec<Payout>> {
+    let slash_recipient = match &auction.bond {
+        Some(bond) => bond.slash_recipient.clone(),
+        None => return Ok(vec![]),
+    };
+
+    let held = BONDS
+        .prefix(auction.id)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, bond)| bond.status == BondStatus::Held)
+        })
+        .map(|item| item.map(|(bidder, _)| bidder))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut payouts = vec![];
+    for bidder in held {
+        let is_winner = auction.winners.iter().any(|winner| winner.bidder == bidder);
+        let unrevealed = auction.sealed
+            && COMMITMENTS
+                .may_load(storage, (auction.id, &bidder))?
+                .is_some_and(|commitment| !commitment.revealed);
+        let status = match auction.phase {
+            AuctionPhase::Closed if is_winner => continue,
+            AuctionPhase::Closed if unrevealed && reveal_expired => BondStatus::Slashed,
+            _ => BondStatus::Returned,
+        };
+
+        let amount = release_bond(storage, auction.id, &bidder, status.clone())?;
+        payouts.push(match status {
+            BondStatus::Slashed => Payout {
+                recipient: slash_recipient.clone(),
+                amount,
+                kind: events::SETTLEMENT_SLASH,
+            },
+            _ => Payout {
+                recipient: bidder,
+                amount,
+                kind: events::SETTLEMENT_REFUND,
+            },
+        });
+    }
+
+    Ok(payouts)
+}
+
+/// Returns the escrowed funds paid out once an auction has closed.
+/// Losing bids and unrevealed commitments are refunded in full, while winners
+/// get back whatever they deposited above the clearing price. In reverse
+/// auctions the buyer gets back the budget the winners are not owed.
+fn escrow_payouts(storage: &dyn Storage, auction: &Auction) -> StdResult<Vec<Payout>> {
+    let mut payouts = vec![];
+
+    if auction.direction == AuctionDirection::Reverse {
+        let spent = total_owed(auction)?;
+        let unspent = auction.budget.checked_sub(spent)?;
+        if !unspent.is_zero() {
+            payouts.push(Payout {
+                recipient: auction.creator.clone(),
+                amount: unspent,
+                kind: events::SETTLEMENT_REFUND,
+            });
+        }
+    }
+
+    let mut deposits = bids()
+        .prefix(auction.id)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, bid)| (bid.bidder, bid.deposit)))
+        .collect::<StdResult<Vec<_>>>()?;
+    deposits.extend(
+        auction
+            .bundle_bids
+            .iter()
+            .map(|bid| (bid.bidder.clone(), bid.deposit)),
+    );
+
+    for (bidder, deposit) in deposits {
+        let winner = auction
+            .winners
+            .iter()
+            .find(|winner| winner.bidder == bidder);
+        // Sellers in reverse auctions escrow nothing and are paid on settlement
+        let refund = match winner {
+            Some(winner) if auction.direction == AuctionDirection::Forward => {
+                deposit.checked_sub(winner.amount_owed)?
+            }
+            _ => deposit,
+        };
+        if !refund.is_zero() {
+            payouts.push(Payout {
+                recipient: bidder,
+                amount: refund,
+                kind: events::SETTLEMENT_REFUND,
+            });
+        }
+    }
+
+    if auction.sealed {
+        for commitment in
+            COMMITMENTS
+                .prefix(auction.id)
+                .range(storage, None, None, Order::Ascending)
+        {
+            let (_, commitment) = commitment?;
+            if !commitment.revealed && !commitment.deposit.is_zero() {
+                payouts.push(Payout {
+                    recipient: commitment.bidder,
+                    amount: commitment.deposit,
+                    kind: events::SETTLEMENT_REFUND,
+                });
+            }
+        }
+    }
+
+    Ok(payouts)
+}
+
+/// Returns the proceeds paid out when a closed auction is settled.
+/// The protocol fee is taken out of the proceeds, so it never adds to what a winner pays.
+/// Forward auctions split the rest of what the winners owe between the beneficiaries by
+/// weight, and the first beneficiary gets any remainder left by rounding down.
+/// Reverse auctions pay each winner out of the budget, less the fee on their payment.
+fn proceeds_payouts(auction: &Auction) -> StdResult<Vec<Payout>> {
+    let mut payouts = vec![];
+
+    match auction.direction {
+        AuctionDirection::Forward => {
+            let proceeds = total_owed(auction)?.checked_sub(auction.protocol_fee)?;
+            let total_weight: u128 = auction
+                .beneficiaries
+                .iter()
+                .map(|beneficiary| beneficiary.weight as u128)
+                .sum();
+            let mut remainder = proceeds;
+            for beneficiary in auction.beneficiaries.iter() {
+                let share = proceeds.multiply_ratio(beneficiary.weight as u128, total_weight);
+                remainder -= share;
+                payouts.push(Payout {
+                    recipient: beneficiary.address.clone(),
+                    amount: share,
+                    kind: events::SETTLEMENT_PAYMENT,
+                });
+            }
+            if let Some(first) = payouts.first_mut() {
+                first.amount += remainder;
+            }
+        }
+        AuctionDirection::Reverse => {
+            for winner in auction.winners.iter() {
+                payouts.push(Payout {
+                    recipient: winner.bidder.clone(),
+                    amount: winner.amount_owed.checked_sub(auction.winner_fee(winner))?,
+                    kind: events::SETTLEMENT_PAYMENT,
+                });
+            }
+        }
+    }
+
+    if let Some(fee) = &auction.fee {
+        payouts.push(Payout {
+            recipient: fee.collector.clone(),
+            amount: auction.protocol_fee,
+            kind: events::SETTLEMENT_FEE,
+        });
+    }
+
+    payouts.retain(|payout| !payout.amount.is_zero());
+    Ok(payouts)
+}
+
+/// Returns the sum of what the auction's winners owe, or are owed in reverse auctions.
+fn total_owed(auction: &Auction) -> StdResult<Uint128> {
+    auction
+        .winners
+        .iter()
+        .try_fold(Uint128::zero(), |total, winner| {
+            Ok(total.checked_add(winner.amount_owed)?)
+        })
+}
+
+fn validate_beneficiaries(
+    deps: Deps,
+    shares: Vec<ProceedsShare>,
+) -> Result<Vec<Beneficiary>, ContractError> {
+    let invalid = ContractError::InvalidBeneficiaries {
+        max: MAX_BENEFICIARIES,
+    };
+    if shares.is_empty() || shares.len() > MAX_BENEFICIARIES {
+        return Err(invalid);
+    }
+
+    let mut beneficiaries: Vec<Beneficiary> = vec![];
+    for share in shares {
+        let address = deps.api.addr_validate(&share.address)?;
+        if share.weight == 0
+            || beneficiaries
+                .iter()
+                .any(|beneficiary| beneficiary.address == address)
+        {
+            return Err(invalid);
+        }
+        beneficiaries.push(Beneficiary {
+            address,
+            weight: share.weight,
+        });
+    }
+    Ok(beneficiaries)
+}
+
+fn validate_bond(deps: Deps, terms: BondTerms) -> Result<BidBond, ContractError> {
+    if terms.amount.is_zero() {
+        return Err(ContractError::InvalidBond {});
+    }
+    Ok(BidBond {
+        amount: terms.amount,
+        slash_recipient: deps.api.addr_validate(&terms.slash_recipient)?,
+    })
+}
+
+/// Checks that bidding opens before it closes, which happens before the reveal
+/// phase closes. Heights and times can't be ordered against each other, so every
+/// point must use the same kind.
+fn validate_schedule(
+    block: &BlockInfo,
+    start: Option<&Scheduled>,
+    end: Option<&Expiration>,
+    reveal_end: Option<&Expiration>,
+    sealed: bool,
+) -> Result<(), ContractError> {
+    if end.is_some_and(|end| end.is_expired(block))
+        || (reveal_end.is_some() && (!sealed || end.is_none()))
+    {
+        return Err(ContractError::InvalidSchedule {});
+    }
+
+    let start = start.map(|start| match start {
+        Scheduled::AtHeight(height) => Expiration::AtHeight(*height),
+        Scheduled::AtTime(time) => Expiration::AtTime(*time),
+    });
+    let points: Vec<&Expiration> = [start.as_ref(), end, reveal_end]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !points.windows(2).all(|pair| pair[0] < pair[1]) {
+        return Err(ContractError::InvalidSchedule {});
+    }
+    Ok(())
+}
+
+fn execute_update_allowed_bidders(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let auction = load_auction(deps.storage, auction_id)?;
+    assert_can_manage(deps.storage, &auction, &info.sender)?;
+
+    if !auction.is_in_progress() {
+        return Err(ContractError::AuctionNotInProgress {});
+    }
+
+    if !auction.restrict_bidders {
+        return Err(ContractError::AuctionNotRestricted {});
+    }
+
+    let add = validate_addresses(deps.as_ref(), add)?;
+    let remove = validate_addresses(deps.as_ref(), remove)?;
+    for bidder in &add {
+        ALLOWED_BIDDERS.save(deps.storage, (auction_id, bidder), &())?;
+    }
+    for bidder in &remove {
+        ALLOWED_BIDDERS.remove(deps.storage, (auction_id, bidder));
+    }
+
+    Ok(Response::new().add_event(events::allowed_bidders_updated(auction_id, &add, &remove)))
+}
+
+fn execute_update_trusted_contracts(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    let add = validate_addresses(deps.as_ref(), add)?;
+    let remove = validate_addresses(deps.as_ref(), remove)?;
+    for contract in &add {
+        TRUSTED_CONTRACTS.save(deps.storage, contract, &())?;
+    }
+    for contract in &remove {
+        TRUSTED_CONTRACTS.remove(deps.storage, contract);
+    }
+
+    Ok(Response::new().add_event(events::trusted_contracts_updated(&add, &remove)))
+}
+
+fn validate_addresses(deps: Deps, addresses: Vec<String>) -> StdResult<Vec<Addr>> {
+    addresses
+        .iter()
+        .map(|address| deps.api.addr_validate(address))
+        .collect()
+}
+
+/// Checks that the auction is accepting bids, or bid commitments, at the current block.
+fn assert_bidding_open(auction: &Auction, block: &BlockInfo) -> Result<(), ContractError> {
+    match auction.current_phase(block) {
+        AuctionPhase::Bidding => Ok(()),
+        AuctionPhase::Pending => Err(ContractError::BiddingNotStarted {}),
+        AuctionPhase::Closed | AuctionPhase::Settled => Err(ContractError::AuctionNotInProgress {}),
+        AuctionPhase::Cancelled => Err(ContractError::AuctionCancelled {}),
+        _ => Err(ContractError::BiddingClosed {}),
+    }
+}
+
+/// Reverse auctions only: asks must leave the budget able to pay every winner.
+fn assert_within_budget(auction: &Auction, amount: Uint128) -> Result<(), ContractError> {
+    let max_ask = auction.max_ask();
+    if amount > max_ask {
+        return Err(ContractError::BidAboveBudget { max_ask });
+    }
+    Ok(())
+}
+
+fn assert_bidder_allowed(
+    storage: &dyn Storage,
+    auction: &Auction,
+    bidder: &Addr,
+) -> Result<(), ContractError> {
+    if auction.restrict_bidders && !ALLOWED_BIDDERS.has(storage, (auction.id, bidder)) {
+        return Err(ContractError::BidderNotAllowed {});
+    }
+    Ok(())
+}
+
+/// Sets or clears the protocol fee charged on auctions started from now on.
+fn execute_update_fee_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee: Option<ProtocolFee>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    let fee = fee
+        .map(|fee| validate_fee(deps.as_ref(), fee))
+        .transpose()?;
+    match &fee {
+        Some(fee) => FEE_CONFIG.save(deps.storage, fee)?,
+        None => FEE_CONFIG.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_event(events::fee_config_updated(fee.as_ref())))
+}
+
+fn validate_fee(deps: Deps, fee: ProtocolFee) -> Result<FeeConfig, ContractError> {
+    if fee.bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee { max: MAX_FEE_BPS });
+    }
+    Ok(FeeConfig {
+        bps: fee.bps,
+        collector: deps.api.addr_validate(&fee.collector)?,
+    })
+}
+
+/// Starts a two step ownership transfer. The new owner must accept it before it takes effect.
+fn execute_propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    PENDING_OWNER.save(deps.storage, &new_owner)?;
+
+    Ok(Response::new().add_event(events::ownership_proposed(&info.sender, &new_owner)))
+}
+
+fn execute_accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    if pending_owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    OWNER.save(deps.storage, &pending_owner)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_event(events::ownership_accepted(&pending_owner)))
+}
+
+/// Leaves the contract without an owner. No new auctions can be started afterwards.
+fn execute_renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    OWNER.remove(deps.storage);
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_event(events::ownership_renounced(&info.sender)))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::QueryGetAuctionWinner { auction_id } => query_get_winner(deps, auction_id.u64()),
+        QueryMsg::QueryGetAuctionWinners { auction_id } => {
+            query_get_winners(deps, auction_id.u64())
+        }
+        QueryMsg::QueryGetBidsForBidder {
+            bidder,
+            start_after,
+            limit,
+        } => query_get_bids_for_bidder(deps, bidder, start_after, limit),
+        QueryMsg::QueryGetBidsForAuction {
+            auction_id,
+            start_after,
+            limit,
+        } => query_get_bids_for_auction(deps, auction_id.u64(), start_after, limit),
+        QueryMsg::QueryGetCurrentAuctionId {} => query_get_current_auction_id(deps),
+        QueryMsg::QueryGetOpenAuctions { start_after, limit } => {
+            query_get_open_auctions(deps, start_after, limit)
+        }
+        QueryMsg::QueryGetOwner {} => query_get_owner(deps),
+        QueryMsg::QueryGetFeeConfig {} => to_json_binary(&QueryFeeConfigResponse {
+            fee: FEE_CONFIG.may_load(deps.storage)?,
+        }),
+        QueryMsg::QueryGetAuctionStatus { auction_id } => {
+            query_get_auction_status(deps, env, auction_id.u64())
+        }
+        QueryMsg::QueryGetAllowedBidders {
+            auction_id,
+            start_after,
+            limit,
+        } => query_get_allowed_bidders(deps, auction_id.u64(), start_after, limit),
+        QueryMsg::QueryGetTrustedContracts { start_after, limit } => {
+            query_get_trusted_contracts(deps, start_after, limit)
+        }
+        QueryMsg::QueryGetBonds {
+            auction_id,
+            start_after,
+            limit,
+        } => query_get_bonds(deps, auction_id.u64(), start_after, limit),
+    }
+}
+
+fn query_get_bids_for_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<Uint64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let bidder = deps.api.addr_validate(&bidder)?;
+
+    let iter = bids().idx.bidder.prefix(bidder.clone()).range(
+        deps.storage,
+        start_after.map(|auction_id| Bound::exclusive((auction_id.u64(), bidder))),
+        None,
+        Order::Ascending,
+    );
+    let bids = cw_paginate::collect(Box::new(iter), limit, |_, bid| Ok::<_, StdError>(bid))?;
+
+    to_json_binary(&QueryBidsForBidderResponse { bids })
+}
+
+fn query_get_bids_for_auction(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<BidCursor>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    if !AUCTIONS.has(deps.storage, auction_id) {
+        return Err(StdError::generic_err(format!(
+            "auction with id {} not found",
+            auction_id
+        )));
+    }
+    // Within an auction the amount index is keyed by amount and then the
+    // bid's own key, so the cursor locates its position even if that bid has
+    // since been withdrawn or amended
+    let min = match start_after {
+        Some(cursor) => {
+            let bidder = deps.api.addr_validate(&cursor.bidder)?;
+            let key = (cursor.amount.u128(), (auction_id, bidder));
+            Some(Bound::ExclusiveRaw(key.joined_key()))
+        }
+        None => None,
+    };
+
+    let iter =
+        bids()
+            .idx
+            .amount
+            .sub_prefix(auction_id)
+            .range(deps.storage, min, None, Order::Ascending);
+    let page: Vec<Bid> =
+        cw_paginate::collect(Box::new(iter), limit, |_, bid| Ok::<_, StdError>(bid))?;
+
+    to_json_binary(&page)
+}
+
+fn query_get_winner(deps: Deps, auction_id: u64) -> StdResult<Binary> {
+    let auction: Auction = AUCTIONS
+        .load(deps.storage, auction_id)
+        .map_err(|_| StdError::generic_err("auction not found"))?;
+
+    assert_not_cancelled(&auction)?;
+    if auction.is_in_progress() {
+        return Err(StdError::generic_err("Auction in progress"));
+    }
+
+    // The winners are determined once, when the auction closes
+    to_json_binary(&QueryAuctionWinnerResponse {
+        winner: auction.winners.into_iter().next(),
+        reserve_binding: auction.reserve_binding,
+        tie_break: auction.tie_break,
+        protocol_fee: auction.protocol_fee,
+    })
+}
+
+fn query_get_winners(deps: Deps, auction_id: u64) -> StdResult<Binary> {
+    let auction: Auction = AUCTIONS
+        .load(deps.storage, auction_id)
+        .map_err(|_| StdError::generic_err("auction not found"))?;
+
+    assert_not_cancelled(&auction)?;
+    if auction.is_in_progress() {
+        return Err(StdError::generic_err("Auction in progress"));
+    }
+
+    to_json_binary(&QueryAuctionWinnersResponse {
+        winners: auction.winners,
+        reserve_binding: auction.reserve_binding,
+        tie_break: auction.tie_break,
+        protocol_fee: auction.protocol_fee,
+    })
+}
+
+/// Cancelled auctions have no winners to report, only why they were cancelled.
+fn assert_not_cancelled(auction: &Auction) -> StdResult<()> {
+    match &auction.cancel_reason {
+        Some(reason) => Err(StdError::generic_err(format!(
+            "Auction was cancelled: {}",
+            reason
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn query_get_current_auction_id(deps: Deps) -> StdResult<Binary> {
+    let auction_id = CURRENT_AUCTION_ID.load(deps.storage)?;
+    to_json_binary(&QueryCurrentAuctionIdResponse {
+        auction_id: auction_id.into(),
+    })
+}
+
+fn query_get_open_auctions(
+    deps: Deps,
+    start_after: Option<Uint64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let auction_ids = OPEN_AUCTIONS
+        .keys(
+            deps.storage,
+            start_after.map(|auction_id| Bound::exclusive(auction_id.u64())),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|auction_id| auction_id.map(Uint64::new))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&QueryOpenAuctionsResponse { auction_ids })
+}
+
+fn query_get_auction_status(deps: Deps, env: Env, auction_id: u64) -> StdResult<Binary> {
+    let auction: Auction = AUCTIONS
+        .load(deps.storage, auction_id)
+        .map_err(|_| StdError::generic_err("auction not found"))?;
+
+    // A secret reserve stays hidden until it is revealed
+    let reserve = match &auction.reserve {
+        Some(Reserve::Secret { amount: None, .. }) => None,
+        _ => auction.reserve_price(),
+    };
+
+    to_json_binary(&QueryAuctionStatusResponse {
+        auction_id: auction_id.into(),
+        direction: auction.direction.clone(),
+        phase: auction.current_phase(&env.block),
+        time_remaining: auction.time_remaining(&env.block),
+        start: auction.bidding_start,
+        end: auction.bidding_end,
+        reveal_end: auction.reveal_end,
+        withdrawals_locked_after: auction.withdrawals_locked_after,
+        reserve,
+        cancel_reason: auction.cancel_reason,
+        beneficiaries: auction.beneficiaries,
+        lot: auction.lot,
+        bond: auction.bond,
+    })
+}
+
+fn query_get_owner(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&QueryOwnerResponse {
+        owner: OWNER.may_load(deps.storage)?,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+fn query_get_allowed_bidders(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start_after = start_after
+        .map(|bidder| deps.api.addr_validate(&bidder))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let bidders = ALLOWED_BIDDERS
+        .prefix(auction_id)
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&QueryAllowedBiddersResponse { bidders })
+}
+
+fn query_get_trusted_contracts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start_after = start_after
+        .map(|contract| deps.api.addr_validate(&contract))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let contracts = TRUSTED_CONTRACTS
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&QueryTrustedContractsResponse { contracts })
+}
+
+fn query_get_bonds(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start_after = start_after
+        .map(|bidder| deps.api.addr_validate(&bidder))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let bonds = BONDS
+        .prefix(auction_id)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(bidder, bond)| BidderBond {
+                bidder,
+                amount: bond.amount,
+                status: bond.status,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&QueryBondsResponse { bonds })
+}
+
+pub fn get_and_increment_auction_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let new_id = CURRENT_AUCTION_ID.update(storage, |id| -> StdResult<_> {
+        let new_id = id + 1;
+        Ok(new_id)
+    })?;
+    Ok(new_id)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::events;
+    use crate::helpers::{bid_commitment, lottery_commitment, lottery_ticket, reserve_commitment};
+    use crate::migrations::v0_1;
+    use crate::msg::{
+        BidCursor, BondTerms, ExecuteMsg, MigrateMsg, ProceedsShare, ProtocolFee,
+        QueryBidsForBidderResponse, QueryFeeConfigResponse, ReceiveMsg, ReceiveNftMsg,
+        ReservePrice, StartAuctionMsg, TieBreakPolicy, UncheckedDenom,
+    };
+    use crate::state::{
+        bids, AuctionDirection, AuctionPhase, Bid, BondStatus, BundleValue, InsufficientBidsPolicy,
+        Lot, TieBreak, Winner, AUCTIONS, CURRENT_AUCTION_ID, OWNER,
+    };
+    use crate::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Coin, Empty, Order, StdError, Timestamp, Uint128, Uint64,
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use cw20::{Cw20Coin, Cw20ExecuteMsg, Denom};
+    use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, SudoMsg};
+    use cw_multi_test::{AppResponse, Executor};
+    use cw_utils::{Duration, Expiration, PaymentError, Scheduled};
+    use rand::Rng;
+
+    fn auction_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(super::execute, super::instantiate, super::query)
+            .with_migrate(super::migrate);
+        Box::new(contract)
+    }
+
+    fn instantiate_auction(app: &mut App) -> Addr {
+        let code_id: u64 = app.store_code(auction_contract());
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &crate::msg::InstantiateMsg {
+                owner: None,
+                fee: None,
+            },
+            &[],
+            "coin",
+            None,
+        )
+        .unwrap()
+    }
+
+    fn start_auction_msg(name: &str) -> StartAuctionMsg {
+        StartAuctionMsg {
+            name: name.to_string(),
+            direction: None,
+            max_num_participants: Uint64::from(10000u64),
+            quantity: None,
+            items: None,
+            sealed: None,
+            bid_denom: UncheckedDenom::Native(DENOM.to_string()),
+            auctioneer: None,
+            allowed_bidders: None,
+            start: None,
+            end: None,
+            reveal_end: None,
+            withdrawals_locked_after: None,
+            allow_amendments: None,
+            on_insufficient_bids: None,
+            reserve: None,
+            tie_break: None,
+            beneficiaries: None,
+            bond: None,
+        }
+    }
+
+    // Returns current auction id
+    fn start_auction(app: &mut App, auction: Addr) -> u64 {
+        // Get current auction ID
+        let resp = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryCurrentAuctionIdResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetCurrentAuctionId {},
+            )
+            .unwrap();
+
+        let prev_auction_id = resp.auction_id.u64();
+
+        // Start auction
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(start_auction_msg("auction_1")),
+            &[],
+        )
+        .unwrap();
+
+        // Ensure current auction ID is correct
+        let resp = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryCurrentAuctionIdResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetCurrentAuctionId {},
+            )
+            .unwrap();
+
+        let current_auction_id = resp.auction_id.u64();
+        assert_eq!(current_auction_id, prev_auction_id + 1);
+        current_auction_id
+    }
+
+    fn close_auction(app: &mut App, auction: Addr, auction_id: u64) {
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction,
+            &ExecuteMsg::ExecuteCloseAuction {
+                auction_id: auction_id.into(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn settle_auction(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+    ) -> Result<AppResponse, anyhow::Error> {
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            auction,
+            &ExecuteMsg::ExecuteSettleAuction {
+                auction_id: auction_id.into(),
+            },
+            &[],
+        )
+    }
+
+    fn bid(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+        bidder: Addr,
+        bid_amount: Uint128,
+    ) -> Result<AppResponse, anyhow::Error> {
+        fund(app, &bidder, bid_amount);
+        app.execute_contract(
+            bidder,
+            auction,
+            &ExecuteMsg::ExecuteBid {
+                auction_id: auction_id.into(),
+                bid_amount,
+            },
+            &coins(bid_amount.u128(), DENOM),
+        )
+    }
+
+    fn bundle_bid(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+        bidder: Addr,
+        bundles: &[(&[u32], u128)],
+    ) -> Result<AppResponse, anyhow::Error> {
+        let bundles: Vec<BundleValue> = bundles
+            .iter()
+            .map(|(items, amount)| BundleValue {
+                items: items.to_vec(),
+                amount: Uint128::new(*amount),
+            })
+            .collect();
+        let deposit = bundles.iter().map(|bundle| bundle.amount).max().unwrap();
+        fund(app, &bidder, deposit);
+        app.execute_contract(
+            bidder,
+            auction,
+            &ExecuteMsg::ExecuteBundleBid {
+                auction_id: auction_id.into(),
+                bundles,
+            },
+            &coins(deposit.u128(), DENOM),
+        )
+    }
+
+    fn commit_bid(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+        bidder: Addr,
+        bid_amount: Uint128,
+        deposit: Uint128,
+        salt: &str,
+    ) -> Result<AppResponse, anyhow::Error> {
+        let commitment = bid_commitment(&bidder, bid_amount, salt);
+        fund(app, &bidder, deposit);
+        app.execute_contract(
+            bidder,
+            auction,
+            &ExecuteMsg::ExecuteCommitBid {
+                auction_id: auction_id.into(),
+                commitment,
+            },
+            &coins(deposit.u128(), DENOM),
+        )
+    }
+
+    fn cw20_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    fn instantiate_cw20(app: &mut App, symbol: &str, initial_balances: Vec<Cw20Coin>) -> Addr {
+        let code_id = app.store_code(cw20_contract());
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Auction Token".to_string(),
+                symbol: symbol.to_string(),
+                decimals: 6,
+                initial_balances,
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            symbol,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn trust_contract(app: &mut App, auction: Addr, contract: Addr) {
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction,
+            &ExecuteMsg::ExecuteUpdateTrustedContracts {
+                add: vec![contract.to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn cw721_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        );
+        Box::new(contract)
+    }
+
+    /// Instantiates a CW721 contract and mints `token_ids` to the admin.
+    fn instantiate_cw721(app: &mut App, token_ids: &[&str]) -> Addr {
+        let code_id = app.store_code(cw721_contract());
+        let nft = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &cw721_base::InstantiateMsg {
+                    name: "Auction Items".to_string(),
+                    symbol: "ITEM".to_string(),
+                    minter: ADMIN.to_string(),
+                },
+                &[],
+                "items",
+                None,
+            )
+            .unwrap();
+        for token_id in token_ids {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                nft.clone(),
+                &cw721_base::ExecuteMsg::<Option<Empty>, Empty>::Mint(cw721_base::MintMsg {
+                    token_id: token_id.to_string(),
+                    owner: ADMIN.to_string(),
+                    token_uri: None,
+                    extension: None,
+                }),
+                &[],
+            )
+            .unwrap();
+        }
+        nft
+    }
+
+    fn nft_owner(app: &App, nft: &Addr, token_id: &str) -> String {
+        app.wrap()
+            .query_wasm_smart::<cw721::OwnerOfResponse>(
+                nft,
+                &cw721_base::QueryMsg::<Empty>::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap()
+            .owner
+    }
+
+    fn nft_auction(
+        app: &mut App,
+        auction: Addr,
+        nft: Addr,
+        token_id: &str,
+        msg: StartAuctionMsg,
+    ) -> Result<AppResponse, anyhow::Error> {
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft,
+            &cw721_base::ExecuteMsg::<Option<Empty>, Empty>::SendNft {
+                contract: auction.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&ReceiveNftMsg::ExecuteStartAuction(msg)).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn cw20_bid(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+        token: Addr,
+        bidder: Addr,
+        bid_amount: Uint128,
+    ) -> Result<AppResponse, anyhow::Error> {
+        app.execute_contract(
+            bidder,
+            token,
+            &Cw20ExecuteMsg::Send {
+                contract: auction.to_string(),
+                amount: bid_amount,
+                msg: to_json_binary(&ReceiveMsg::ExecuteBid {
+                    auction_id: auction_id.into(),
+                    bid_amount,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn cw20_balance(app: &App, token: &Addr, address: &Addr) -> Uint128 {
+        app.wrap()
+            .query_wasm_smart::<cw20::BalanceResponse>(
+                token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    }
+
+    fn fund(app: &mut App, recipient: &Addr, amount: Uint128) {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), DENOM),
+        }))
+        .unwrap();
+    }
+
+    fn balance(app: &App, address: &Addr) -> Uint128 {
+        app.wrap().query_balance(address, DENOM).unwrap().amount
+    }
+
+    fn reveal_bid(
+        app: &mut App,
+        auction: Addr,
+        auction_id: u64,
+        bidder: Addr,
+        amount: Uint128,
+        salt: &str,
+    ) -> Result<AppResponse, anyhow::Error> {
+        app.execute_contract(
+            bidder,
+            auction,
+            &ExecuteMsg::ExecuteRevealBid {
+                auction_id: auction_id.into(),
+                amount,
+                salt: salt.to_string(),
+            },
+            &[],
+        )
+    }
+
+    fn event_attr(res: &AppResponse, ty: &str, key: &str) -> Vec<String> {
+        res.events
+            .iter()
+            .filter(|ev| ev.ty == format!("wasm-{}", ty))
+            .flat_map(|ev| ev.attributes.iter())
+            .filter(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+            .collect()
+    }
+
+    fn query_bonds(app: &App, auction: &Addr, auction_id: u64) -> Vec<(Addr, BondStatus)> {
+        app.wrap()
+            .query_wasm_smart::<crate::msg::QueryBondsResponse>(
+                auction,
+                &crate::msg::QueryMsg::QueryGetBonds {
+                    auction_id: auction_id.into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .bonds
+            .into_iter()
+            .map(|bond| (bond.bidder, bond.status))
+            .collect()
+    }
+
+    const ADMIN: &str = "admin";
+    const DENOM: &str = "ujunox";
+
+    #[test]
+    fn test_auction_start_and_close() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let auction_id = start_auction(&mut app, auction.clone());
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            Addr::unchecked("bidder1"),
+            Uint128::new(10),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            Addr::unchecked("bidder2"),
+            Uint128::new(20),
+        )
+        .unwrap();
+        close_auction(&mut app, auction, auction_id);
+    }
+
+    #[test]
+    fn test_query_bids_for_bidder() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let first_auction_id = start_auction(&mut app, auction.clone());
+
+        let bidder1 = Addr::unchecked("bidder1");
+
+        // Place bids
+        bid(
+            &mut app,
+            auction.clone(),
+            first_auction_id,
+            bidder1.clone(),
+            Uint128::from(10u128),
+        )
+        .unwrap();
+
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            first_auction_id,
+            bidder1.clone(),
+            Uint128::from(20u128),
+        )
+        .unwrap_err();
+
+        // assert can only bid once
+        let err_str = format!("{:?}", err);
+        assert!(err_str.contains("Participant has already placed a bid for this auction"));
+
+        let bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart::<QueryBidsForBidderResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder1.clone().into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .bids;
+
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].auction_id, first_auction_id);
+        assert_eq!(bids[0].bidder, bidder1);
+
+        // The first auction stays open while the second one runs
+        let new_auction_id = start_auction(&mut app, auction.clone());
+
+        // bid again
+        bid(
+            &mut app,
+            auction.clone(),
+            new_auction_id,
+            bidder1.clone(),
+            Uint128::from(20u128),
+        )
+        .unwrap();
+
+        let bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart::<QueryBidsForBidderResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder1.clone().into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .bids;
+
+        assert_eq!(bids.len(), 2);
+
+        assert_eq!(bids[0].auction_id, first_auction_id);
+        assert_eq!(bids[0].bidder, bidder1);
+
+        assert_eq!(bids[1].auction_id, new_auction_id);
+        assert_eq!(bids[1].bidder, bidder1);
+
+        let bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart::<QueryBidsForBidderResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder1.clone().into(),
+                    start_after: Some(first_auction_id.into()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap()
+            .bids;
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].auction_id, new_auction_id);
+
+        // for bid in bids.iter() {
+        //     assert_eq!(bid.auction_id, auction_id);
+        //     assert_eq!(bid.bidder, bidder1);
+        // }
+    }
+
+    #[test]
+    fn test_auction_with_one_bid() {
+        let mut app = App::default();
+        let code_id = app.store_code(auction_contract());
+        let auction = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &crate::msg::InstantiateMsg {
+                    owner: None,
+                    fee: None,
+                },
+                &[],
+                "coin",
+                None,
+            )
+            .unwrap();
+
+        let auction_id = start_auction(&mut app, auction.clone());
+
+        // Place bid
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            Addr::unchecked("bidder1"),
+            Uint128::from(100u128),
+        )
+        .unwrap();
+
+        // A single bid cannot set a second price, so closing is rejected
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: auction_id.into(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "There must be at least 2 participants"
+        );
+
+        let winner_res = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(
+            winner_res,
+            StdError::generic_err("Querier contract error: Generic error: Auction in progress")
+        );
+    }
+
+    #[test]
+    fn test_multi_unit_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                    max_num_participants: Uint64::new(2),
+                    quantity: Some(Uint64::new(2)),
+                    ..start_auction_msg("too many items")
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Quantity must be at least 1 and below the maximum number of participants"
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                quantity: Some(Uint64::new(2)),
+                ..start_auction_msg("two slots")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+        let bidder4 = Addr::unchecked("bidder4");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(10),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(40),
+        )
+        .unwrap();
+
+        // Two items need a third bid to set the price
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "There must be at least 3 participants"
+        );
+
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder3.clone(),
+            Uint128::new(30),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder4.clone(),
+            Uint128::new(20),
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 1);
+
+        let winners = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnersResponse>(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinners {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap()
+            .winners;
+        assert_eq!(
+            winners,
+            vec![
+                Winner {
+                    auction_id: 1,
+                    amount_owed: Uint128::new(20),
+                    bidder: bidder2.clone(),
+                    bundle: None,
+                },
+                Winner {
+                    auction_id: 1,
+                    amount_owed: Uint128::new(20),
+                    bidder: bidder3.clone(),
+                    bundle: None,
+                },
+            ]
+        );
+
+        // Both winners pay the highest losing bid, the losers are refunded in full
+        assert_eq!(balance(&app, &bidder1), Uint128::new(10));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(20));
+        assert_eq!(balance(&app, &bidder3), Uint128::new(10));
+        assert_eq!(balance(&app, &bidder4), Uint128::new(20));
+    }
+
+    #[test]
+    fn test_combinatorial_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                    items: Some(vec!["a".to_string(), "b".to_string()]),
+                    ..start_auction_msg("too many bidders")
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidCombinatorialAuction {}.to_string()
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                max_num_participants: Uint64::new(10),
+                items: Some(vec!["a".to_string(), "b".to_string()]),
+                ..start_auction_msg("bundles")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+        let bidder4 = Addr::unchecked("bidder4");
+
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Auction only accepts bids on bundles of items"
+        );
+        let err = bundle_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            &[(&[0, 0], 10)],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Bundles must name distinct items sold in the auction"
+        );
+        let err =
+            bundle_bid(&mut app, auction.clone(), 1, bidder1.clone(), &[(&[2], 10)]).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Bundles must name distinct items sold in the auction"
+        );
+
+        bundle_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            &[(&[0, 1], 100)],
+        )
+        .unwrap();
+        bundle_bid(&mut app, auction.clone(), 1, bidder2.clone(), &[(&[0], 60)]).unwrap();
+        bundle_bid(&mut app, auction.clone(), 1, bidder3.clone(), &[(&[1], 50)]).unwrap();
+        bundle_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder4.clone(),
+            &[(&[0], 55), (&[1], 45)],
+        )
+        .unwrap();
+        // The failed bids above minted funds that were never escrowed
+        let bidder1_start = balance(&app, &bidder1);
+
+        close_auction(&mut app, auction.clone(), 1);
+
+        // Selling the items separately to bidder2 and bidder3 is worth 110, more
+        // than any other allocation. Without bidder2 the others could have had 105
+        // (bidder4 and bidder3) rather than the 50 they get, so bidder2 owes 55.
+        // Without bidder3 they could have had 105 (bidder2 and bidder4) rather than 60.
+        let winners = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnersResponse>(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinners {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap()
+            .winners;
+        assert_eq!(
+            winners,
+            vec![
+                Winner {
+                    auction_id: 1,
+                    amount_owed: Uint128::new(55),
+                    bidder: bidder2.clone(),
+                    bundle: Some(vec![0]),
+                },
+                Winner {
+                    auction_id: 1,
+                    amount_owed: Uint128::new(45),
+                    bidder: bidder3.clone(),
+                    bundle: Some(vec![1]),
+                },
+            ]
+        );
+
+        assert_eq!(balance(&app, &bidder1), bidder1_start + Uint128::new(100));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(5));
+        assert_eq!(balance(&app, &bidder3), Uint128::new(5));
+        assert_eq!(balance(&app, &bidder4), Uint128::new(55));
+    }
+
+    #[test]
+    fn test_reverse_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let admin = Addr::unchecked(ADMIN);
+
+        // The buyer escrows a budget of 100 for 2 items, so asks are capped at 50
+        fund(&mut app, &admin, Uint128::new(100));
+        app.execute_contract(
+            admin.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                direction: Some(AuctionDirection::Reverse),
+                quantity: Some(Uint64::new(2)),
+                ..start_auction_msg("procurement")
+            }),
+            &coins(100, DENOM),
+        )
+        .unwrap();
+
+        let seller1 = Addr::unchecked("seller1");
+        let seller2 = Addr::unchecked("seller2");
+        let seller3 = Addr::unchecked("seller3");
+        let ask = |app: &mut App, seller: &Addr, amount: u128, funds: &[Coin]| {
+            app.execute_contract(
+                seller.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteBid {
+                    auction_id: Uint64::new(1),
+                    bid_amount: Uint128::new(amount),
+                },
+                funds,
+            )
+        };
+
+        let err = ask(&mut app, &seller1, 60, &[]).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::BidAboveBudget {
+                max_ask: Uint128::new(50)
+            }
+            .to_string()
+        );
+        fund(&mut app, &seller1, Uint128::new(20));
+        let err = ask(&mut app, &seller1, 20, &coins(20, DENOM)).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Payment(PaymentError::NonPayable {}).to_string()
+        );
+
+        ask(&mut app, &seller1, 20, &[]).unwrap();
+        ask(&mut app, &seller2, 30, &[]).unwrap();
+        ask(&mut app, &seller3, 40, &[]).unwrap();
+        close_auction(&mut app, auction.clone(), 1);
+
+        // The two lowest asks win and are each paid the lowest losing ask
+        let res: crate::msg::QueryAuctionWinnersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinners {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        let winners: Vec<_> = res
+            .winners
+            .iter()
+            .map(|winner| (winner.bidder.clone(), winner.amount_owed))
+            .collect();
+        assert_eq!(
+            winners,
+            vec![
+                (seller1.clone(), Uint128::new(40)),
+                (seller2.clone(), Uint128::new(40))
+            ]
+        );
+
+        // Closing refunds the unspent budget, settling pays the winners
+        assert_eq!(balance(&app, &admin), Uint128::new(20));
+        assert_eq!(balance(&app, &seller1), Uint128::new(20));
+        app.execute_contract(
+            admin.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteSettleAuction {
+                auction_id: Uint64::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &seller1), Uint128::new(60));
+        assert_eq!(balance(&app, &seller2), Uint128::new(40));
+        assert_eq!(balance(&app, &seller3), Uint128::zero());
+        assert_eq!(balance(&app, &admin), Uint128::new(20));
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let bidder_a = Addr::unchecked("bidder_a");
+        let bidder_b = Addr::unchecked("bidder_b");
+        let bidder_c = Addr::unchecked("bidder_c");
+
+        // bidder_b and bidder_a tie at 50, in that order
+        let start_with_tied_bids = |app: &mut App, tie_break: Option<TieBreakPolicy>| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                    tie_break,
+                    ..start_auction_msg("tie break")
+                }),
+                &[],
+            )
+            .unwrap();
+            let auction_id = app
+                .wrap()
+                .query_wasm_smart::<crate::msg::QueryCurrentAuctionIdResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetCurrentAuctionId {},
+                )
+                .unwrap()
+                .auction_id
+                .u64();
+            for (bidder, amount) in [(&bidder_b, 50), (&bidder_a, 50), (&bidder_c, 10)] {
+                bid(
+                    app,
+                    auction.clone(),
+                    auction_id,
+                    bidder.clone(),
+                    Uint128::new(amount),
+                )
+                .unwrap();
+            }
+            auction_id
+        };
+        let winner = |app: &App, auction_id: u64| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                        auction_id: auction_id.into(),
+                    },
+                )
+                .unwrap()
+        };
+
+        for (tie_break, expected) in [
+            (None, &bidder_a),
+            (Some(TieBreakPolicy::EarliestBid), &bidder_b),
+            (Some(TieBreakPolicy::LatestBid), &bidder_a),
+            (Some(TieBreakPolicy::LowestAddress), &bidder_a),
+        ] {
+            let auction_id = start_with_tied_bids(&mut app, tie_break);
+            close_auction(&mut app, auction.clone(), auction_id);
+            let res = winner(&app, auction_id);
+            let won = res.winner.unwrap();
+            assert_eq!(&won.bidder, expected);
+            assert_eq!(won.amount_owed, Uint128::new(50));
+        }
+
+        let auction_id = start_with_tied_bids(
+            &mut app,
+            Some(TieBreakPolicy::Lottery(lottery_commitment("seed"))),
+        );
+        let close = |app: &mut App| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: auction_id.into(),
+                },
+                &[],
+            )
+        };
+        let reveal = |app: &mut App, seed: &str| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteRevealLotterySeed {
+                    auction_id: auction_id.into(),
+                    seed: seed.to_string(),
+                },
+                &[],
+            )
+        };
+
+        let err = close(&mut app).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::LotterySeedNotRevealed {}.to_string()
+        );
+        let err = reveal(&mut app, "wrong").unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::LotterySeedMismatch {}.to_string()
+        );
+        reveal(&mut app, "seed").unwrap();
+        close(&mut app).unwrap();
+
+        // The tied bid with the lower ticket wins, and the seed is published
+        let expected = if lottery_ticket("seed", 0) < lottery_ticket("seed", 1) {
+            &bidder_b
+        } else {
+            &bidder_a
+        };
+        let res = winner(&app, auction_id);
+        assert_eq!(&res.winner.unwrap().bidder, expected);
+        assert_eq!(
+            res.tie_break,
+            TieBreak::Lottery {
+                commitment: lottery_commitment("seed"),
+                seed: Some("seed".to_string()),
+            }
+        );
+
+        // If the seed is never revealed anyone may close the auction once it
+        // ends, and the earliest of the tied bids wins
+        let height = app.block_info().height;
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                tie_break: Some(TieBreakPolicy::Lottery(lottery_commitment("unrevealed"))),
+                end: Some(Expiration::AtHeight(height + 10)),
+                ..start_auction_msg("unrevealed lottery")
+            }),
+            &[],
+        )
+        .unwrap();
+        let auction_id = auction_id + 1;
+        for bidder in [&bidder_b, &bidder_a] {
+            bid(
+                &mut app,
+                auction.clone(),
+                auction_id,
+                bidder.clone(),
+                Uint128::new(50),
+            )
+            .unwrap();
+        }
+        let close_as = |app: &mut App, sender: &Addr| {
+            app.execute_contract(
+                sender.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: auction_id.into(),
+                },
+                &[],
+            )
+        };
+        let err = close_as(&mut app, &Addr::unchecked(ADMIN)).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::LotterySeedNotRevealed {}.to_string()
+        );
+        app.update_block(|block| block.height += 10);
+        close_as(&mut app, &bidder_c).unwrap();
+        let res = winner(&app, auction_id);
+        assert_eq!(&res.winner.unwrap().bidder, &bidder_b);
+
+        // When fewer items are left than tied bids, the tie-break picks among all of them
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                quantity: Some(Uint64::new(2)),
+                tie_break: Some(TieBreakPolicy::EarliestBid),
+                ..start_auction_msg("partial tie")
+            }),
+            &[],
+        )
+        .unwrap();
+        let auction_id = auction_id + 1;
+        let bidder_d = Addr::unchecked("bidder_d");
+        for (bidder, amount) in [
+            (&bidder_a, 50),
+            (&bidder_c, 50),
+            (&bidder_b, 50),
+            (&bidder_d, 60),
+        ] {
+            bid(
+                &mut app,
+                auction.clone(),
+                auction_id,
+                bidder.clone(),
+                Uint128::new(amount),
+            )
+            .unwrap();
+        }
+        close_auction(&mut app, auction.clone(), auction_id);
+        let winners: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnersResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinners {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap()
+            .winners
+            .into_iter()
+            .map(|winner| winner.bidder)
+            .collect();
+        assert_eq!(winners, vec![bidder_d, bidder_a.clone()]);
+    }
+
+    #[test]
+    fn test_withdraw_bid() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let height = app.block_info().height;
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                max_num_participants: Uint64::new(2),
+                withdrawals_locked_after: Some(Expiration::AtHeight(height + 10)),
+                ..start_auction_msg("withdrawals")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+        let withdraw = |app: &mut App, bidder: &Addr| {
+            app.execute_contract(
+                bidder.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteWithdrawBid {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+        };
+
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(30),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(20),
+        )
+        .unwrap();
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder3.clone(),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Max number of auction participants has already been reached"
+        );
+
+        // Withdrawing refunds the bid and frees up the bidder's place
+        withdraw(&mut app, &bidder1).unwrap();
+        assert_eq!(balance(&app, &bidder1), Uint128::new(30));
+        let err = withdraw(&mut app, &bidder1).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::BidNotFound {}.to_string()
+        );
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder3.clone(),
+            Uint128::new(10),
+        )
+        .unwrap();
+
+        let bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart::<QueryBidsForBidderResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder1.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .bids;
+        assert!(bids.is_empty());
+
+        // Past the cutoff bids are locked in
+        app.update_block(|block| block.height += 10);
+        let err = withdraw(&mut app, &bidder2).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::WithdrawalsLocked {}.to_string()
+        );
+
+        close_auction(&mut app, auction.clone(), 1);
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        let winner = res.winner.unwrap();
+        assert_eq!(winner.bidder, bidder2);
+        assert_eq!(winner.amount_owed, Uint128::new(10));
+    }
+
+    #[test]
+    fn test_update_bid() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let auction_id = start_auction(&mut app, auction.clone());
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let update = |app: &mut App, bidder: &Addr, amount: u128, funds: &[Coin]| {
+            app.execute_contract(
+                bidder.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateBid {
+                    auction_id: auction_id.into(),
+                    new_amount: Uint128::new(amount),
+                },
+                funds,
+            )
+        };
+
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(30),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(20),
+        )
+        .unwrap();
+
+        // Raising a bid takes only the difference
+        fund(&mut app, &bidder2, Uint128::new(20));
+        let err = update(&mut app, &bidder2, 40, &coins(10, DENOM)).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Attached funds (10) do not match the bid amount (20)"
+        );
+        update(&mut app, &bidder2, 40, &coins(20, DENOM)).unwrap();
+
+        // Lowering a bid refunds the difference
+        update(&mut app, &bidder1, 10, &[]).unwrap();
+        assert_eq!(balance(&app, &bidder1), Uint128::new(20));
+
+        let bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart::<QueryBidsForBidderResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder2.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .bids;
+        assert_eq!(bids[0].amount, Uint128::new(40));
+        assert_eq!(bids[0].deposit, Uint128::new(40));
+        assert_eq!(bids[0].amendments, 1);
+
+        // The raised bid now wins and pays the lowered one
+        close_auction(&mut app, auction.clone(), auction_id);
+        let winner = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap()
+            .winner
+            .unwrap();
+        assert_eq!(winner.bidder, bidder2);
+        assert_eq!(winner.amount_owed, Uint128::new(10));
+        assert_eq!(balance(&app, &bidder1), Uint128::new(30));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(30));
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                allow_amendments: Some(false),
+                ..start_auction_msg("final bids")
+            }),
+            &[],
+        )
+        .unwrap();
+        let auction_id = auction_id + 1;
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(30),
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateBid {
+                    auction_id: auction_id.into(),
+                    new_amount: Uint128::new(10),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AmendmentsDisabled {}.to_string()
+        );
+
+        // Once withdrawals are locked a seller can lower their ask but not raise it
+        let admin = Addr::unchecked(ADMIN);
+        let height = app.block_info().height;
+        fund(&mut app, &admin, Uint128::new(100));
+        app.execute_contract(
+            admin.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                direction: Some(AuctionDirection::Reverse),
+                withdrawals_locked_after: Some(Expiration::AtHeight(height + 5)),
+                ..start_auction_msg("procurement")
+            }),
+            &coins(100, DENOM),
+        )
+        .unwrap();
+        let auction_id = auction_id + 1;
+        let update = |app: &mut App, bidder: &Addr, amount: u128| {
+            app.execute_contract(
+                bidder.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateBid {
+                    auction_id: auction_id.into(),
+                    new_amount: Uint128::new(amount),
+                },
+                &[],
+            )
+        };
+        for (seller, amount) in [(&bidder1, 40), (&bidder2, 50)] {
+            app.execute_contract(
+                seller.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteBid {
+                    auction_id: auction_id.into(),
+                    bid_amount: Uint128::new(amount),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        app.update_block(|block| block.height += 5);
+        let err = update(&mut app, &bidder1, 60).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::WithdrawalsLocked {}.to_string()
+        );
+        update(&mut app, &bidder1, 30).unwrap();
+    }
+
+    #[test]
+    fn test_public_reserve() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let reserve_auction = StartAuctionMsg {
+            reserve: Some(ReservePrice::Public(Uint128::new(50))),
+            ..start_auction_msg("reserve")
+        };
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(reserve_auction.clone()),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(80),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(30),
+        )
+        .unwrap();
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        let closed = res
+            .events
+            .iter()
+            .find(|ev| ev.ty == format!("wasm-{}", events::AUCTION_CLOSED))
+            .unwrap();
+        assert!(closed
+            .attributes
+            .iter()
+            .any(|attr| attr.key == events::ATTR_RESERVE_BINDING && attr.value == "true"));
+
+        // The reserve is above the second price, so the winner pays the reserve
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        let winner = res.winner.unwrap();
+        assert_eq!(winner.bidder, bidder1);
+        assert_eq!(winner.amount_owed, Uint128::new(50));
+        assert!(res.reserve_binding);
+        assert_eq!(balance(&app, &bidder1), Uint128::new(30));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(30));
+
+        // A single bid below the reserve closes without a sale
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(reserve_auction),
+            &[],
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            2,
+            bidder2.clone(),
+            Uint128::new(20),
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 2);
+
+        let res: crate::msg::QueryAuctionWinnersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinners {
+                    auction_id: Uint64::new(2),
+                },
+            )
+            .unwrap();
+        assert!(res.winners.is_empty());
+        assert!(res.reserve_binding);
+        assert_eq!(balance(&app, &bidder2), Uint128::new(50));
+    }
+
+    #[test]
+    fn test_secret_reserve() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                reserve: Some(ReservePrice::Secret(reserve_commitment(
+                    Uint128::new(50),
+                    "salt",
+                ))),
+                ..start_auction_msg("secret reserve")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(80),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(60),
+        )
+        .unwrap();
+
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(status.reserve, None);
+
+        let reveal = |amount: u128| ExecuteMsg::ExecuteRevealReserve {
+            auction_id: Uint64::new(1),
+            amount: Uint128::new(amount),
+            salt: "salt".to_string(),
+        };
+        let err = app
+            .execute_contract(bidder1.clone(), auction.clone(), &reveal(50), &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Unauthorized");
+        let err = app
+            .execute_contract(Addr::unchecked(ADMIN), auction.clone(), &reveal(40), &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Revealed reserve does not match its commitment"
+        );
+        app.execute_contract(Addr::unchecked(ADMIN), auction.clone(), &reveal(50), &[])
+            .unwrap();
+
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(status.reserve, Some(Uint128::new(50)));
+
+        close_auction(&mut app, auction.clone(), 1);
+
+        // Both bids cleared the reserve, so the second price applies
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        let winner = res.winner.unwrap();
+        assert_eq!(winner.bidder, bidder1);
+        assert_eq!(winner.amount_owed, Uint128::new(60));
+        assert!(!res.reserve_binding);
+    }
+
+    #[test]
+    fn test_concurrent_auctions() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let first_auction_id = start_auction(&mut app, auction.clone());
+        let second_auction_id = start_auction(&mut app, auction.clone());
+
+        let query_open = |app: &App, start_after: Option<u64>| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryOpenAuctionsResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetOpenAuctions {
+                        start_after: start_after.map(Uint64::new),
+                        limit: None,
+                    },
+                )
+                .unwrap()
+                .auction_ids
+        };
+        assert_eq!(
+            query_open(&app, None),
+            vec![
+                Uint64::new(first_auction_id),
+                Uint64::new(second_auction_id)
+            ]
+        );
+        assert_eq!(
+            query_open(&app, Some(first_auction_id)),
+            vec![Uint64::new(second_auction_id)]
+        );
+
+        // The same bidders take part in both auctions
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        for (auction_id, amounts) in [(first_auction_id, [10, 20]), (second_auction_id, [40, 30])] {
+            bid(
+                &mut app,
+                auction.clone(),
+                auction_id,
+                bidder1.clone(),
+                Uint128::new(amounts[0]),
+            )
+            .unwrap();
+            bid(
+                &mut app,
+                auction.clone(),
+                auction_id,
+                bidder2.clone(),
+                Uint128::new(amounts[1]),
+            )
+            .unwrap();
+        }
+
+        close_auction(&mut app, auction.clone(), first_auction_id);
+        assert_eq!(query_open(&app, None), vec![Uint64::new(second_auction_id)]);
+
+        // Closing one auction leaves the other open for bids
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            first_auction_id,
+            Addr::unchecked("bidder3"),
+            Uint128::new(50),
+        )
+        .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Auction is not in progress");
+        bid(
+            &mut app,
+            auction.clone(),
+            second_auction_id,
+            Addr::unchecked("bidder3"),
+            Uint128::new(50),
+        )
+        .unwrap();
+
+        close_auction(&mut app, auction.clone(), second_auction_id);
+        assert!(query_open(&app, None).is_empty());
+
+        let winner = |app: &App, auction_id: u64| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                        auction_id: auction_id.into(),
+                    },
+                )
+                .unwrap()
+                .winner
+                .unwrap()
+        };
+        assert_eq!(winner(&app, first_auction_id).bidder, bidder2);
+        assert_eq!(
+            winner(&app, second_auction_id).bidder,
+            Addr::unchecked("bidder3")
+        );
+    }
+
+    #[test]
+    fn test_close_without_sale() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+                ..start_auction_msg("no sale")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &bidder1), Uint128::zero());
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        let closed = res
+            .events
+            .iter()
+            .find(|ev| ev.ty == format!("wasm-{}", events::AUCTION_CLOSED))
+            .unwrap();
+        assert!(closed
+            .attributes
+            .iter()
+            .any(|attr| attr.key == events::ATTR_WINNER_COUNT && attr.value == "0"));
+        assert!(!res
+            .events
+            .iter()
+            .any(|ev| ev.ty == format!("wasm-{}", events::WINNER_DETERMINED)));
+
+        // Nobody won, so the only bid is refunded in full
+        assert_eq!(balance(&app, &bidder1), Uint128::new(100));
+
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.winner, None);
+    }
+
+    #[test]
+    fn test_find_winner() {
+        let mut app = App::default();
+        let code_id = app.store_code(auction_contract());
+        let auction = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &crate::msg::InstantiateMsg {
+                    owner: None,
+                    fee: None,
+                },
+                &[],
+                "coin",
+                None,
+            )
+            .unwrap();
+
+        let auction_id = start_auction(&mut app, auction.clone());
+
+        let mut bids: Vec<Bid> = Vec::new();
+        let num_bids = rand::thread_rng().gen_range(2..1000);
+        for i in 1..=num_bids {
+            let bidder_name = format!("bidder{}", i);
+            let bid_amount = Uint128::from(u128::from(
+                u32::try_from(rand::thread_rng().gen_range(1..1000)).unwrap(),
+            ));
+            bid(
+                &mut app,
+                auction.clone(),
+                auction_id,
+                Addr::unchecked(&bidder_name),
+                bid_amount,
+            )
+            .unwrap();
+            bids.push(Bid {
+                auction_id,
+                amount: bid_amount,
+                bidder: Addr::unchecked(&bidder_name),
+                timestamp: Timestamp::from_seconds(0),
+                sequence: i - 1,
+                deposit: bid_amount,
+                amendments: 0,
+                last_updated: Timestamp::from_seconds(0),
+            });
+        }
+
+        // Page through the bids for the auction
+        let mut queried_bids: Vec<Bid> = vec![];
+        loop {
+            let page: Vec<Bid> = app
+                .wrap()
+                .query_wasm_smart(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetBidsForAuction {
+                        auction_id: auction_id.into(),
+                        start_after: queried_bids.last().map(|bid| BidCursor {
+                            amount: bid.amount,
+                            bidder: bid.bidder.to_string(),
+                        }),
+                        limit: Some(30),
+                    },
+                )
+                .unwrap();
+            if page.is_empty() {
+                break;
+            }
+            assert!(page.len() <= 30);
+            queried_bids.extend(page);
+        }
+
+        assert_eq!(queried_bids.len(), bids.len());
+        assert!(queried_bids
+            .windows(2)
+            .all(|pair| (pair[0].amount, &pair[0].bidder) < (pair[1].amount, &pair[1].bidder)));
+
+        // A cursor still pages on from where its bid was after it is withdrawn
+        let withdrawn = queried_bids[0].clone();
+        app.execute_contract(
+            withdrawn.bidder.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteWithdrawBid {
+                auction_id: auction_id.into(),
+            },
+            &[],
+        )
+        .unwrap();
+        let page: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForAuction {
+                    auction_id: auction_id.into(),
+                    start_after: Some(BidCursor {
+                        amount: withdrawn.amount,
+                        bidder: withdrawn.bidder.to_string(),
+                    }),
+                    limit: Some(30),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            page,
+            queried_bids[1..]
+                .iter()
+                .take(30)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        bids.retain(|bid| bid.bidder != withdrawn.bidder);
+
+        // Close auction
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteCloseAuction {
+                auction_id: auction_id.into(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Query winner for auction
+        let winner: Winner = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap()
+            .winner
+            .unwrap();
+
+        let winning_bid = bids.iter().max_by(|a, b| a.amount.cmp(&b.amount)).unwrap();
+        assert_eq!(winner.bidder, winning_bid.bidder);
+    }
+
+    #[test]
+    fn test_sealed_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                sealed: Some(true),
+                ..start_auction_msg("sealed")
+            }),
+            &[],
+        )
+        .unwrap();
+        let auction_id = 1u64;
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+        let bidder4 = Addr::unchecked("bidder4");
+
+        // Plain bids are rejected by sealed auctions
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bids for this auction are sealed"));
+
+        commit_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(10),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap();
+        commit_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(30),
+            Uint128::new(30),
+            "b",
+        )
+        .unwrap();
+        commit_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder3.clone(),
+            Uint128::new(20),
+            Uint128::new(20),
+            "c",
+        )
+        .unwrap();
+        commit_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder4.clone(),
+            Uint128::new(99),
+            Uint128::new(150),
+            "d",
+        )
+        .unwrap();
+
+        // Bids can't be revealed before the reveal phase
+        let err = reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Auction is not in its reveal phase"));
+
+        // Amounts are hidden while committing
+        let queried_bids: Vec<Bid> = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetBidsForAuction {
+                    auction_id: auction_id.into(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(queried_bids.is_empty());
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartReveal {
+                auction_id: auction_id.into(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // No more commitments once the reveal phase has started
+        let err = commit_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            Addr::unchecked("bidder5"),
+            Uint128::new(50),
+            Uint128::new(50),
+            "e",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bidding is closed for this auction"));
+
+        // Revealing a different amount or salt fails
+        let err = reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(31),
+            "b",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Revealed bid does not match the commitment"));
+        let err = reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(30),
+            "x",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Revealed bid does not match the commitment"));
+
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap();
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(30),
+            "b",
+        )
+        .unwrap();
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder3.clone(),
+            Uint128::new(20),
+            "c",
+        )
+        .unwrap();
+
+        let err = reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bid has already been revealed"));
+
+        // bidder4 never reveals their bid of 99
+        close_auction(&mut app, auction.clone(), auction_id);
+
+        let err = reveal_bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder4.clone(),
+            Uint128::new(99),
+            "d",
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Auction is not in its reveal phase"));
+
+        let winner: Winner = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap()
+            .winner
+            .unwrap();
+        assert_eq!(winner.bidder, bidder2);
+        assert_eq!(winner.amount_owed, Uint128::new(20));
+
+        // Losers and the unrevealed commitment are refunded in full,
+        // the winner gets back everything above the second price.
+        // bidder1 also kept the 10 funded for the rejected plain bid.
+        assert_eq!(balance(&app, &bidder1), Uint128::new(20));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(10));
+        assert_eq!(balance(&app, &bidder3), Uint128::new(20));
+        assert_eq!(balance(&app, &bidder4), Uint128::new(150));
+        assert_eq!(balance(&app, &auction), Uint128::new(20));
+    }
+
+    #[test]
+    fn test_bids_are_escrowed() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let auction_id = start_auction(&mut app, auction.clone());
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+
+        // Attached funds must match the bid
+        fund(&mut app, &bidder1, Uint128::new(100));
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteBid {
+                    auction_id: auction_id.into(),
+                    bid_amount: Uint128::new(100),
+                },
+                &coins(50, DENOM),
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("do not match the bid amount"));
+
+        // Funds must be in the auction's denom
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteBid {
+                    auction_id: auction_id.into(),
+                    bid_amount: Uint128::new(100),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("No funds sent"));
+
+        app.execute_contract(
+            bidder1.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteBid {
+                auction_id: auction_id.into(),
+                bid_amount: Uint128::new(100),
+            },
+            &coins(100, DENOM),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder3.clone(),
+            Uint128::new(40),
+        )
+        .unwrap();
+
+        assert_eq!(balance(&app, &auction), Uint128::new(210));
+        assert_eq!(balance(&app, &bidder1), Uint128::zero());
+
+        close_auction(&mut app, auction.clone(), auction_id);
+
+        // The winner pays the second price, everyone else gets their bid back
+        assert_eq!(balance(&app, &bidder1), Uint128::new(30));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(70));
+        assert_eq!(balance(&app, &bidder3), Uint128::new(40));
+        assert_eq!(balance(&app, &auction), Uint128::new(70));
+    }
+
+    #[test]
+    fn test_settle_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let treasury = Addr::unchecked("treasury");
+        let auctioneer = Addr::unchecked("auctioneer");
+
+        let start = |app: &mut App, beneficiaries: Vec<(&Addr, u32)>| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                    beneficiaries: Some(
+                        beneficiaries
+                            .into_iter()
+                            .map(|(address, weight)| ProceedsShare {
+                                address: address.to_string(),
+                                weight,
+                            })
+                            .collect(),
+                    ),
+                    ..start_auction_msg("shared proceeds")
+                }),
+                &[],
+            )
+        };
+
+        for beneficiaries in [
+            vec![],
+            vec![(&treasury, 0)],
+            vec![(&treasury, 1), (&treasury, 2)],
+        ] {
+            let err = start(&mut app, beneficiaries).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                ContractError::InvalidBeneficiaries { max: 10 }.to_string()
+            );
+        }
+
+        start(&mut app, vec![(&treasury, 2), (&auctioneer, 1)]).unwrap();
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+
+        let err = settle_auction(&mut app, auction.clone(), 1).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionNotClosed {}.to_string()
+        );
+
+        // The clearing price stays in escrow until the auction is settled
+        close_auction(&mut app, auction.clone(), 1);
+        assert_eq!(balance(&app, &auction), Uint128::new(70));
+
+        // 70 splits 2:1 into 46 and 23, and the first beneficiary gets the remainder
+        let res = settle_auction(&mut app, auction.clone(), 1).unwrap();
+        assert_eq!(
+            event_attr(&res, events::AUCTION_SETTLED, events::ATTR_PROCEEDS),
+            vec!["70"]
+        );
+        assert_eq!(balance(&app, &treasury), Uint128::new(47));
+        assert_eq!(balance(&app, &auctioneer), Uint128::new(23));
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+
+        let err = settle_auction(&mut app, auction.clone(), 1).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionAlreadySettled {}.to_string()
+        );
+
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(status.phase, AuctionPhase::Settled);
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.winner.unwrap().bidder, bidder1);
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let mut app = App::default();
+        let code_id = app.store_code(auction_contract());
+        let collector = Addr::unchecked("collector");
+        let err = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &crate::msg::InstantiateMsg {
+                    owner: None,
+                    fee: Some(ProtocolFee {
+                        bps: 10_001,
+                        collector: collector.to_string(),
+                    }),
+                },
+                &[],
+                "auction",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidFee { max: 10_000 }.to_string()
+        );
+        let auction = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &crate::msg::InstantiateMsg {
+                    owner: None,
+                    fee: Some(ProtocolFee {
+                        bps: 250,
+                        collector: collector.to_string(),
+                    }),
+                },
+                &[],
+                "auction",
+                None,
+            )
+            .unwrap();
+
+        let auction_id = start_auction(&mut app, auction.clone());
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(1000),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(800),
+        )
+        .unwrap();
+
+        // Fee changes only apply to auctions started afterwards
+        let update_fee = |app: &mut App, sender: &str, fee: Option<ProtocolFee>| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateFeeConfig { fee },
+                &[],
+            )
+        };
+        let err = update_fee(&mut app, "bidder1", None).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+        update_fee(&mut app, ADMIN, None).unwrap();
+        let res: QueryFeeConfigResponse = app
+            .wrap()
+            .query_wasm_smart(auction.clone(), &crate::msg::QueryMsg::QueryGetFeeConfig {})
+            .unwrap();
+        assert_eq!(res.fee, None);
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: auction_id.into(),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::AUCTION_CLOSED, events::ATTR_PROTOCOL_FEE),
+            vec!["20"]
+        );
+        let res: crate::msg::QueryAuctionWinnerResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.protocol_fee, Uint128::new(20));
+
+        // The winner pays the second price, and the fee comes out of the seller's proceeds
+        settle_auction(&mut app, auction.clone(), auction_id).unwrap();
+        assert_eq!(balance(&app, &bidder1), Uint128::new(200));
+        assert_eq!(balance(&app, &collector), Uint128::new(20));
+        assert_eq!(balance(&app, &Addr::unchecked(ADMIN)), Uint128::new(780));
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+    }
+
+    #[test]
+    fn test_cancel_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let auction_id = start_auction(&mut app, auction.clone());
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+
+        let cancel = ExecuteMsg::ExecuteCancelAuction {
+            auction_id: auction_id.into(),
+            reason: "item withdrawn".to_string(),
+        };
+
+        // Only the owner or auctioneer may cancel
+        let err = app
+            .execute_contract(bidder1.clone(), auction.clone(), &cancel, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+
+        let res = app
+            .execute_contract(Addr::unchecked(ADMIN), auction.clone(), &cancel, &[])
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::AUCTION_CANCELLED, events::ATTR_REASON),
+            vec!["item withdrawn"]
+        );
+
+        // Every bid is refunded in full
+        assert_eq!(balance(&app, &bidder1), Uint128::new(100));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(70));
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+
+        let err = app
+            .execute_contract(Addr::unchecked(ADMIN), auction.clone(), &cancel, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionCancelled {}.to_string()
+        );
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: auction_id.into(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionCancelled {}.to_string()
+        );
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            Addr::unchecked("bidder3"),
+            Uint128::new(50),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionCancelled {}.to_string()
+        );
+
+        let err = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionWinner {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Auction was cancelled: item withdrawn"));
+
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction,
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: auction_id.into(),
+                },
+            )
+            .unwrap();
+        assert_eq!(status.phase, AuctionPhase::Cancelled);
+        assert_eq!(status.cancel_reason, Some("item withdrawn".to_string()));
+    }
+
+    #[test]
+    fn test_events() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(start_auction_msg("auction_1")),
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::AUCTION_STARTED, events::ATTR_AUCTION_ID),
+            vec!["1"]
+        );
+        assert_eq!(
+            event_attr(&res, events::AUCTION_STARTED, events::ATTR_DIRECTION),
+            vec!["forward"]
+        );
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+
+        let res = bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(
+            event_attr(&res, events::BID_PLACED, events::ATTR_BIDDER),
+            vec!["bidder1"]
+        );
+        assert_eq!(
+            event_attr(&res, events::BID_PLACED, events::ATTR_AMOUNT),
+            vec!["100"]
+        );
+        assert_eq!(
+            event_attr(&res, events::BID_PLACED, events::ATTR_PARTICIPANT_COUNT),
+            vec!["1"]
+        );
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder3.clone(),
+            Uint128::new(40),
+        )
+        .unwrap();
+
+        let res = app
+            .execute_contract(
+                bidder3.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteWithdrawBid {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::BID_WITHDRAWN, events::ATTR_PARTICIPANT_COUNT),
+            vec!["2"]
+        );
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::AUCTION_CLOSED, events::ATTR_CLEARING_PRICE),
+            vec!["70"]
+        );
+        assert_eq!(
+            event_attr(&res, events::AUCTION_CLOSED, events::ATTR_PARTICIPANT_COUNT),
+            vec!["2"]
+        );
+        assert_eq!(
+            event_attr(&res, events::WINNER_DETERMINED, events::ATTR_BIDDER),
+            vec!["bidder1"]
+        );
+        assert_eq!(
+            event_attr(&res, events::WINNER_DETERMINED, events::ATTR_AMOUNT_OWED),
+            vec!["70"]
+        );
+
+        // The winner gets back what they bid above the price, the loser their whole bid
+        assert_eq!(
+            event_attr(&res, events::SETTLEMENT, events::ATTR_RECIPIENT),
+            vec!["bidder1", "bidder2"]
+        );
+        assert_eq!(
+            event_attr(&res, events::SETTLEMENT, events::ATTR_AMOUNT),
+            vec!["30", "70"]
+        );
+        assert_eq!(
+            event_attr(&res, events::SETTLEMENT, events::ATTR_KIND),
+            vec![events::SETTLEMENT_REFUND, events::SETTLEMENT_REFUND]
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                allowed_bidders: Some(vec![bidder1.to_string()]),
+                ..start_auction_msg("auction_2")
+            }),
+            &[],
+        )
+        .unwrap();
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateAllowedBidders {
+                    auction_id: Uint64::new(2),
+                    add: vec![bidder2.to_string(), bidder3.to_string()],
+                    remove: vec![bidder1.to_string()],
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(
+                &res,
+                events::ALLOWED_BIDDERS_UPDATED,
+                events::ATTR_AUCTION_ID
+            ),
+            vec!["2"]
+        );
+        assert_eq!(
+            event_attr(&res, events::ALLOWED_BIDDERS_UPDATED, events::ATTR_ADDED),
+            vec!["bidder2,bidder3"]
+        );
+        assert_eq!(
+            event_attr(&res, events::ALLOWED_BIDDERS_UPDATED, events::ATTR_REMOVED),
+            vec!["bidder1"]
+        );
+
+        // Changes to the contract's configuration aren't about any one auction
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateTrustedContracts {
+                    add: vec!["token".to_string()],
+                    remove: vec![],
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::TRUSTED_CONTRACTS_UPDATED, events::ATTR_ADDED),
+            vec!["token"]
+        );
+        assert!(event_attr(
+            &res,
+            events::TRUSTED_CONTRACTS_UPDATED,
+            events::ATTR_REMOVED
+        )
+        .is_empty());
+        assert!(event_attr(
+            &res,
+            events::TRUSTED_CONTRACTS_UPDATED,
+            events::ATTR_AUCTION_ID
+        )
+        .is_empty());
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateFeeConfig {
+                    fee: Some(ProtocolFee {
+                        bps: 250,
+                        collector: "collector".to_string(),
+                    }),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::FEE_CONFIG_UPDATED, events::ATTR_FEE_BPS),
+            vec!["250"]
+        );
+        assert_eq!(
+            event_attr(&res, events::FEE_CONFIG_UPDATED, events::ATTR_FEE_COLLECTOR),
+            vec!["collector"]
+        );
+
+        let new_owner = Addr::unchecked("new_owner");
+        let res = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteProposeNewOwner {
+                    new_owner: new_owner.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::OWNERSHIP_PROPOSED, events::ATTR_PENDING_OWNER),
+            vec!["new_owner"]
+        );
+        let res = app
+            .execute_contract(
+                new_owner.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteAcceptOwnership {},
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(&res, events::OWNERSHIP_ACCEPTED, events::ATTR_OWNER),
+            vec!["new_owner"]
+        );
+        let res = app
+            .execute_contract(
+                new_owner.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteRenounceOwnership {},
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            event_attr(
+                &res,
+                events::OWNERSHIP_RENOUNCED,
+                events::ATTR_PREVIOUS_OWNER
+            ),
+            vec!["new_owner"]
+        );
+    }
+
+    #[test]
+    fn test_cw20_budget() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let token = instantiate_cw20(
+            &mut app,
+            "BUY",
+            vec![Cw20Coin {
+                address: ADMIN.to_string(),
+                amount: Uint128::new(100),
+            }],
+        );
+        let start_msg = to_json_binary(&ReceiveMsg::ExecuteStartAuction(StartAuctionMsg {
+            direction: Some(AuctionDirection::Reverse),
+            bid_denom: UncheckedDenom::Cw20(token.to_string()),
+            ..start_auction_msg("cw20 budget")
+        }))
+        .unwrap();
+        let send_budget = |app: &mut App| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                token.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: auction.to_string(),
+                    amount: Uint128::new(100),
+                    msg: start_msg.clone(),
+                },
+                &[],
+            )
+        };
+
+        // Only trusted tokens can start auctions
+        let err = send_budget(&mut app).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::UntrustedContract {}.to_string()
+        );
+
+        // Calling Receive directly can't impersonate the owner
+        let err = app
+            .execute_contract(
+                Addr::unchecked("attacker"),
+                auction.clone(),
+                &ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                    sender: ADMIN.to_string(),
+                    amount: Uint128::new(100),
+                    msg: start_msg.clone(),
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::UntrustedContract {}.to_string()
+        );
+
+        trust_contract(&mut app, auction.clone(), token.clone());
+        send_budget(&mut app).unwrap();
+        let contracts = app
+            .wrap()
+            .query_wasm_smart::<crate::msg::QueryTrustedContractsResponse>(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetTrustedContracts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .contracts;
+        assert_eq!(contracts, vec![token.clone()]);
+        let balance = app
+            .wrap()
+            .query_wasm_smart::<cw20::BalanceResponse>(
+                token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: auction.to_string(),
+                },
+            )
+            .unwrap()
+            .balance;
+        assert_eq!(balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_nft_auction() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let nft = instantiate_cw721(&mut app, &["sold", "unsold", "cancelled"]);
+
+        // Only trusted CW721 contracts can start auctions
+        let err = nft_auction(
+            &mut app,
+            auction.clone(),
+            nft.clone(),
+            "sold",
+            start_auction_msg("untrusted"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::UntrustedContract {}.to_string()
+        );
+        assert_eq!(nft_owner(&app, &nft, "sold"), ADMIN);
+        trust_contract(&mut app, auction.clone(), nft.clone());
+
+        let err = nft_auction(
+            &mut app,
+            auction.clone(),
+            nft.clone(),
+            "sold",
+            StartAuctionMsg {
+                quantity: Some(Uint64::new(2)),
+                ..start_auction_msg("two of one nft")
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidLot {}.to_string()
+        );
+
+        // The NFT stays in escrow until the auction settles, then goes to the winner
+        nft_auction(
+            &mut app,
+            auction.clone(),
+            nft.clone(),
+            "sold",
+            start_auction_msg("sold"),
+        )
+        .unwrap();
+        assert_eq!(nft_owner(&app, &nft, "sold"), auction.to_string());
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            status.lot,
+            Some(Lot::Nft {
+                contract: nft.clone(),
+                token_id: "sold".to_string(),
+            })
+        );
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 1);
+        assert_eq!(nft_owner(&app, &nft, "sold"), auction.to_string());
+        let res = settle_auction(&mut app, auction.clone(), 1).unwrap();
+        assert_eq!(
+            event_attr(&res, events::LOT_TRANSFERRED, events::ATTR_TOKEN_ID),
+            vec!["sold"]
+        );
+        assert_eq!(nft_owner(&app, &nft, "sold"), bidder1.to_string());
+
+        // Without a sale the NFT goes back to the seller
+        nft_auction(
+            &mut app,
+            auction.clone(),
+            nft.clone(),
+            "unsold",
+            StartAuctionMsg {
+                on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+                ..start_auction_msg("unsold")
+            },
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 2);
+        assert_eq!(nft_owner(&app, &nft, "unsold"), ADMIN);
+
+        nft_auction(
+            &mut app,
+            auction.clone(),
+            nft.clone(),
+            "cancelled",
+            start_auction_msg("cancelled"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction,
+            &ExecuteMsg::ExecuteCancelAuction {
+                auction_id: Uint64::new(3),
+                reason: "changed my mind".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(nft_owner(&app, &nft, "cancelled"), ADMIN);
+    }
+
+    #[test]
+    fn test_token_lot() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let admin = Addr::unchecked(ADMIN);
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: ADMIN.to_string(),
+            amount: coins(1000, "ulot"),
+        }))
+        .unwrap();
+        let lot_balance =
+            |app: &App, address: &Addr| app.wrap().query_balance(address, "ulot").unwrap().amount;
+
+        let err = app
+            .execute_contract(
+                admin.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                    quantity: Some(Uint64::new(2)),
+                    ..start_auction_msg("split lot")
+                }),
+                &coins(500, "ulot"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidLot {}.to_string()
+        );
+
+        // Native coins attached to a forward auction are escrowed as its lot
+        app.execute_contract(
+            admin.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(start_auction_msg("native lot")),
+            &coins(500, "ulot"),
+        )
+        .unwrap();
+        assert_eq!(lot_balance(&app, &auction), Uint128::new(500));
+        let status: crate::msg::QueryAuctionStatusResponse = app
+            .wrap()
+            .query_wasm_smart(
+                auction.clone(),
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            status.lot,
+            Some(Lot::Tokens {
+                denom: Denom::Native("ulot".to_string()),
+                amount: Uint128::new(500),
+            })
+        );
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(70),
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 1);
+        settle_auction(&mut app, auction.clone(), 1).unwrap();
+        assert_eq!(lot_balance(&app, &bidder1), Uint128::new(500));
+        assert_eq!(lot_balance(&app, &auction), Uint128::zero());
+        assert_eq!(balance(&app, &admin), Uint128::new(70));
+
+        // A CW20 lot goes back to the seller if the auction doesn't clear
+        let token = instantiate_cw20(
+            &mut app,
+            "LOT",
+            vec![Cw20Coin {
+                address: ADMIN.to_string(),
+                amount: Uint128::new(300),
+            }],
+        );
+        let token_balance = |app: &App, address: &Addr| {
+            app.wrap()
+                .query_wasm_smart::<cw20::BalanceResponse>(
+                    token.clone(),
+                    &cw20::Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap()
+                .balance
+        };
+        trust_contract(&mut app, auction.clone(), token.clone());
+        app.execute_contract(
+            admin.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: auction.to_string(),
+                amount: Uint128::new(300),
+                msg: to_json_binary(&ReceiveMsg::ExecuteStartAuction(StartAuctionMsg {
+                    on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+                    ..start_auction_msg("cw20 lot")
+                }))
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(token_balance(&app, &auction), Uint128::new(300));
+        bid(
+            &mut app,
+            auction.clone(),
+            2,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 2);
+        assert_eq!(token_balance(&app, &admin), Uint128::new(300));
+        assert_eq!(token_balance(&app, &auction), Uint128::zero());
+    }
+
+    #[test]
+    fn test_bid_bonds() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let slasher = Addr::unchecked("slasher");
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                sealed: Some(true),
+                bond: Some(BondTerms {
+                    amount: Uint128::new(10),
+                    slash_recipient: slasher.to_string(),
+                }),
+                ..start_auction_msg("bonded")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+        let bidder4 = Addr::unchecked("bidder4");
+
+        // Commitments carry the bond on top of the deposit for the bid
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCommitBid {
+                    auction_id: Uint64::new(1),
+                    commitment: bid_commitment(&bidder1, Uint128::new(100), "a"),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::MissingBond {
+                bond: Uint128::new(10)
+            }
+            .to_string()
+        );
+        let err = commit_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+            Uint128::new(5),
+            "a",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InsufficientBond {
+                bond: Uint128::new(10),
+                deposit: Uint128::new(5)
+            }
+            .to_string()
+        );
+
+        for (bidder, amount, salt) in [
+            (&bidder1, 100, "a"),
+            (&bidder2, 50, "b"),
+            (&bidder3, 70, "c"),
+            (&bidder4, 30, "d"),
+        ] {
+            commit_bid(
+                &mut app,
+                auction.clone(),
+                1,
+                bidder.clone(),
+                Uint128::new(amount),
+                Uint128::new(amount + 10),
+                salt,
+            )
+            .unwrap();
+        }
+
+        // Withdrawing while bidding is open gives the bond back with the deposit
+        app.execute_contract(
+            bidder4.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteWithdrawBid {
+                auction_id: Uint64::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &bidder4), Uint128::new(40));
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartReveal {
+                auction_id: Uint64::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+            "a",
+        )
+        .unwrap();
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(50),
+            "b",
+        )
+        .unwrap();
+        close_auction(&mut app, auction.clone(), 1);
+
+        // bidder3 never revealed, so their bond is slashed and only their deposit refunded.
+        // The winner's bond is held until the auction settles.
+        assert_eq!(
+            query_bonds(&app, &auction, 1),
+            vec![
+                (bidder1.clone(), BondStatus::Held),
+                (bidder2.clone(), BondStatus::Returned),
+                (bidder3.clone(), BondStatus::Slashed),
+                (bidder4.clone(), BondStatus::Returned),
+            ]
+        );
+        assert_eq!(balance(&app, &slasher), Uint128::new(10));
+        assert_eq!(balance(&app, &bidder3), Uint128::new(70));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(60));
+        // 5 left over from the failed commitment, plus 100 - 50 refunded
+        assert_eq!(balance(&app, &bidder1), Uint128::new(55));
+
+        // Forward auction winners paid into escrow and cannot default
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteDeclareDefault {
+                    auction_id: Uint64::new(1),
+                    bidder: bidder1.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::WinnerCannotDefault {}.to_string()
+        );
+
+        settle_auction(&mut app, auction.clone(), 1).unwrap();
+        assert_eq!(
+            query_bonds(&app, &auction, 1)[0],
+            (bidder1.clone(), BondStatus::Returned)
+        );
+        assert_eq!(balance(&app, &bidder1), Uint128::new(65));
+        assert_eq!(balance(&app, &Addr::unchecked(ADMIN)), Uint128::new(50));
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+    }
+
+    #[test]
+    fn test_bonds_reveal_window() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let slasher = Addr::unchecked("slasher");
+        let height = app.block_info().height;
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                sealed: Some(true),
+                end: Some(Expiration::AtHeight(height + 10)),
+                reveal_end: Some(Expiration::AtHeight(height + 20)),
+                on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+                bond: Some(BondTerms {
+                    amount: Uint128::new(10),
+                    slash_recipient: slasher.to_string(),
+                }),
+                ..start_auction_msg("bonded")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        for (bidder, amount, salt) in [(&bidder1, 100, "a"), (&bidder2, 50, "b")] {
+            commit_bid(
+                &mut app,
+                auction.clone(),
+                1,
+                bidder.clone(),
+                Uint128::new(amount),
+                Uint128::new(amount + 10),
+                salt,
+            )
+            .unwrap();
+        }
+
+        app.update_block(|block| block.height += 10);
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(100),
+            "a",
+        )
+        .unwrap();
+
+        // Closing before bidder2's reveal window is over can't slash their bond
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::RevealPhaseOpen {}.to_string()
+        );
+        assert_eq!(
+            query_bonds(&app, &auction, 1),
+            vec![
+                (bidder1.clone(), BondStatus::Held),
+                (bidder2.clone(), BondStatus::Held),
+            ]
+        );
+
+        // Once the window has run out the unrevealed bond is slashed
+        app.update_block(|block| block.height += 10);
+        close_auction(&mut app, auction.clone(), 1);
+        assert_eq!(
+            query_bonds(&app, &auction, 1),
+            vec![
+                (bidder1.clone(), BondStatus::Returned),
+                (bidder2.clone(), BondStatus::Slashed),
+            ]
+        );
+        assert_eq!(balance(&app, &slasher), Uint128::new(10));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(50));
+    }
 
     #[test]
-    fn test_query_bids_for_bidder() {
+    fn test_winner_default() {
         let mut app = App::default();
         let auction = instantiate_auction(&mut app);
-        let first_auction_id = start_auction(&mut app, auction.clone());
+        let admin = Addr::unchecked(ADMIN);
+        let slasher = Addr::unchecked("slasher");
+
+        fund(&mut app, &admin, Uint128::new(100));
+        app.execute_contract(
+            admin.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                direction: Some(AuctionDirection::Reverse),
+                quantity: Some(Uint64::new(2)),
+                bond: Some(BondTerms {
+                    amount: Uint128::new(5),
+                    slash_recipient: slasher.to_string(),
+                }),
+                ..start_auction_msg("procurement")
+            }),
+            &coins(100, DENOM),
+        )
+        .unwrap();
+
+        let seller1 = Addr::unchecked("seller1");
+        let seller2 = Addr::unchecked("seller2");
+        let seller3 = Addr::unchecked("seller3");
+        let ask = |app: &mut App, seller: &Addr, amount: u128, funds: &[Coin]| {
+            app.execute_contract(
+                seller.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteBid {
+                    auction_id: Uint64::new(1),
+                    bid_amount: Uint128::new(amount),
+                },
+                funds,
+            )
+        };
+        let declare_default = |app: &mut App, sender: &Addr, bidder: &Addr| {
+            app.execute_contract(
+                sender.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteDeclareDefault {
+                    auction_id: Uint64::new(1),
+                    bidder: bidder.to_string(),
+                },
+                &[],
+            )
+        };
+        let settle = |app: &mut App, sender: &Addr| {
+            app.execute_contract(
+                sender.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteSettleAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+        };
+
+        // Asks escrow nothing but the bond
+        let err = ask(&mut app, &seller1, 20, &[]).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::MissingBond {
+                bond: Uint128::new(5)
+            }
+            .to_string()
+        );
+        fund(&mut app, &seller1, Uint128::new(8));
+        let err = ask(&mut app, &seller1, 20, &coins(8, DENOM)).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::BidFundsMismatch {
+                bid_amount: Uint128::zero(),
+                deposit: Uint128::new(3)
+            }
+            .to_string()
+        );
+
+        fund(&mut app, &seller2, Uint128::new(5));
+        fund(&mut app, &seller3, Uint128::new(5));
+        ask(&mut app, &seller1, 20, &coins(5, DENOM)).unwrap();
+        ask(&mut app, &seller2, 30, &coins(5, DENOM)).unwrap();
+        ask(&mut app, &seller3, 40, &coins(5, DENOM)).unwrap();
+
+        let err = declare_default(&mut app, &admin, &seller2).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionNotClosed {}.to_string()
+        );
+
+        // seller1 and seller2 win at 40 each, seller3 gets their bond back
+        close_auction(&mut app, auction.clone(), 1);
+        assert_eq!(balance(&app, &seller3), Uint128::new(5));
+        assert_eq!(balance(&app, &admin), Uint128::new(20));
+
+        // A winner can't get paid by settling before the buyer had the chance to declare a default
+        let err = settle(&mut app, &seller2).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+
+        let err = declare_default(&mut app, &seller1, &seller2).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+        let err = declare_default(&mut app, &admin, &seller3).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::NotAWinner {}.to_string()
+        );
+
+        // seller2 failed to deliver: their bond is slashed and their payment goes back to the buyer
+        let res = declare_default(&mut app, &admin, &seller2).unwrap();
+        assert_eq!(
+            event_attr(&res, events::WINNER_DEFAULTED, events::ATTR_BIDDER),
+            vec![seller2.to_string()]
+        );
+        assert_eq!(balance(&app, &slasher), Uint128::new(5));
+        assert_eq!(balance(&app, &admin), Uint128::new(60));
+        let err = declare_default(&mut app, &admin, &seller2).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::NotAWinner {}.to_string()
+        );
+
+        settle(&mut app, &admin).unwrap();
+        // 3 left over from the rejected ask, the payment of 40 and the bond of 5
+        assert_eq!(balance(&app, &seller1), Uint128::new(48));
+        assert_eq!(balance(&app, &seller2), Uint128::zero());
+        assert_eq!(
+            query_bonds(&app, &auction, 1),
+            vec![
+                (seller1.clone(), BondStatus::Returned),
+                (seller2.clone(), BondStatus::Slashed),
+                (seller3.clone(), BondStatus::Returned),
+            ]
+        );
+        assert_eq!(balance(&app, &auction), Uint128::zero());
+
+        let err = declare_default(&mut app, &admin, &seller1).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::AuctionAlreadySettled {}.to_string()
+        );
+    }
+
+    #[test]
+    fn test_cw20_bids() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let token = instantiate_cw20(
+            &mut app,
+            "GOV",
+            vec![
+                Cw20Coin {
+                    address: bidder1.to_string(),
+                    amount: Uint128::new(100),
+                },
+                Cw20Coin {
+                    address: bidder2.to_string(),
+                    amount: Uint128::new(100),
+                },
+            ],
+        );
+        let other_token = instantiate_cw20(
+            &mut app,
+            "OTHER",
+            vec![Cw20Coin {
+                address: bidder1.to_string(),
+                amount: Uint128::new(100),
+            }],
+        );
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                bid_denom: UncheckedDenom::Cw20(token.to_string()),
+                ..start_auction_msg("cw20")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        // Bids in anything other than the auction's token are rejected
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(60),
+        )
+        .unwrap_err();
+        assert!(
+            format!("{:?}", err).contains("Bid was paid in a token this auction does not accept")
+        );
+        let err = cw20_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            other_token.clone(),
+            bidder1.clone(),
+            Uint128::new(60),
+        )
+        .unwrap_err();
+        assert!(
+            format!("{:?}", err).contains("Bid was paid in a token this auction does not accept")
+        );
+
+        cw20_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            token.clone(),
+            bidder1.clone(),
+            Uint128::new(60),
+        )
+        .unwrap();
+        cw20_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            token.clone(),
+            bidder2.clone(),
+            Uint128::new(40),
+        )
+        .unwrap();
+        assert_eq!(cw20_balance(&app, &token, &auction), Uint128::new(100));
+
+        close_auction(&mut app, auction.clone(), 1);
+
+        assert_eq!(cw20_balance(&app, &token, &bidder1), Uint128::new(60));
+        assert_eq!(cw20_balance(&app, &token, &bidder2), Uint128::new(100));
+        assert_eq!(cw20_balance(&app, &token, &auction), Uint128::new(40));
+        assert_eq!(
+            cw20_balance(&app, &other_token, &bidder1),
+            Uint128::new(100)
+        );
+    }
+
+    #[test]
+    fn test_access_control() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let auctioneer = Addr::unchecked("auctioneer");
+        let stranger = Addr::unchecked("stranger");
+
+        let start_msg = ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+            auctioneer: Some(auctioneer.to_string()),
+            on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+            ..start_auction_msg("managed")
+        });
+
+        // Only the owner may start auctions
+        let err = app
+            .execute_contract(stranger.clone(), auction.clone(), &start_msg, &[])
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+        let err = app
+            .execute_contract(auctioneer.clone(), auction.clone(), &start_msg, &[])
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+        app.execute_contract(Addr::unchecked(ADMIN), auction.clone(), &start_msg, &[])
+            .unwrap();
+
+        // The auctioneer may close the auction, nobody else besides the owner
+        let err = app
+            .execute_contract(
+                stranger.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+        app.execute_contract(
+            auctioneer.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteCloseAuction {
+                auction_id: Uint64::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_ownership_transfer() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let new_owner = Addr::unchecked("new_owner");
+
+        let query_owner = |app: &App| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryOwnerResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetOwner {},
+                )
+                .unwrap()
+        };
+        assert_eq!(query_owner(&app).owner, Some(Addr::unchecked(ADMIN)));
+
+        // Only the owner may propose a new owner
+        let err = app
+            .execute_contract(
+                new_owner.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteProposeNewOwner {
+                    new_owner: new_owner.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+
+        let err = app
+            .execute_contract(
+                new_owner.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteAcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("No ownership transfer is pending"));
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteProposeNewOwner {
+                new_owner: new_owner.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Ownership doesn't move until the proposal is accepted
+        let owner = query_owner(&app);
+        assert_eq!(owner.owner, Some(Addr::unchecked(ADMIN)));
+        assert_eq!(owner.pending_owner, Some(new_owner.clone()));
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("stranger"),
+                auction.clone(),
+                &ExecuteMsg::ExecuteAcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+
+        app.execute_contract(
+            new_owner.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteAcceptOwnership {},
+            &[],
+        )
+        .unwrap();
+        let owner = query_owner(&app);
+        assert_eq!(owner.owner, Some(new_owner.clone()));
+        assert_eq!(owner.pending_owner, None);
+
+        // After renouncing nobody can start auctions
+        app.execute_contract(
+            new_owner.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteRenounceOwnership {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_owner(&app).owner, None);
+
+        let err = app
+            .execute_contract(
+                new_owner,
+                auction.clone(),
+                &ExecuteMsg::ExecuteStartAuction(start_auction_msg("orphaned")),
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+    }
+
+    #[test]
+    fn test_allowed_bidders() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let bidder3 = Addr::unchecked("bidder3");
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                allowed_bidders: Some(vec![bidder1.to_string(), bidder2.to_string()]),
+                ..start_auction_msg("private")
+            }),
+            &[],
+        )
+        .unwrap();
+        let auction_id = Uint64::from(1u64);
+
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder1.clone(),
+            Uint128::new(10),
+        )
+        .unwrap();
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder3.clone(),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bidder is not allowed to bid in this auction"));
+
+        // Only the owner or auctioneer may update the list
+        let update_msg = ExecuteMsg::ExecuteUpdateAllowedBidders {
+            auction_id,
+            add: vec![bidder3.to_string()],
+            remove: vec![bidder2.to_string()],
+        };
+        let err = app
+            .execute_contract(bidder1.clone(), auction.clone(), &update_msg, &[])
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
+        app.execute_contract(Addr::unchecked(ADMIN), auction.clone(), &update_msg, &[])
+            .unwrap();
+
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder2.clone(),
+            Uint128::new(10),
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bidder is not allowed to bid in this auction"));
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder3.clone(),
+            Uint128::new(10),
+        )
+        .unwrap();
+
+        let query_allowed = |app: &App, start_after: Option<String>, limit: Option<u32>| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryAllowedBiddersResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetAllowedBidders {
+                        auction_id,
+                        start_after,
+                        limit,
+                    },
+                )
+                .unwrap()
+                .bidders
+        };
+        assert_eq!(
+            query_allowed(&app, None, None),
+            vec![bidder1.clone(), bidder3.clone()]
+        );
+        assert_eq!(query_allowed(&app, None, Some(1)), vec![bidder1.clone()]);
+        assert_eq!(
+            query_allowed(&app, Some(bidder1.to_string()), None),
+            vec![bidder3.clone()]
+        );
+
+        // Bidders taken off the list keep their bid but can no longer change it
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteUpdateAllowedBidders {
+                auction_id,
+                add: vec![],
+                remove: vec![bidder1.to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateBid {
+                    auction_id,
+                    new_amount: Uint128::new(5),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::BidderNotAllowed {}.to_string()
+        );
+
+        // Auctions without an allowlist can't be given one after they start
+        let open_auction_id = start_auction(&mut app, auction.clone());
+        let err = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                auction.clone(),
+                &ExecuteMsg::ExecuteUpdateAllowedBidders {
+                    auction_id: open_auction_id.into(),
+                    add: vec![bidder1.to_string()],
+                    remove: vec![],
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Auction does not restrict who may bid"));
+    }
 
+    #[test]
+    fn test_bidding_window() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
         let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
 
-        // Place bids
-        bid(
-            &mut app,
+        let now = app.block_info().time;
+        let height = app.block_info().height;
+
+        // Bidding must open before it closes, which comes before the reveal phase
+        // closes, and the schedule can't mix heights with times
+        for schedule in [
+            StartAuctionMsg {
+                end: Some(Expiration::AtTime(now)),
+                ..start_auction_msg("already ended")
+            },
+            StartAuctionMsg {
+                start: Some(Scheduled::AtTime(now.plus_seconds(200))),
+                end: Some(Expiration::AtTime(now.plus_seconds(100))),
+                ..start_auction_msg("ends before it starts")
+            },
+            StartAuctionMsg {
+                start: Some(Scheduled::AtHeight(height + 10)),
+                end: Some(Expiration::AtTime(now.plus_seconds(200))),
+                ..start_auction_msg("height and time")
+            },
+            StartAuctionMsg {
+                sealed: Some(true),
+                end: Some(Expiration::AtHeight(height + 20)),
+                reveal_end: Some(Expiration::AtHeight(height + 10)),
+                ..start_auction_msg("reveal ends first")
+            },
+        ] {
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    auction.clone(),
+                    &ExecuteMsg::ExecuteStartAuction(schedule),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                ContractError::InvalidSchedule {}.to_string()
+            );
+        }
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
             auction.clone(),
-            bidder1.clone(),
-            Uint128::from(10u128),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                start: Some(Scheduled::AtTime(now.plus_seconds(100))),
+                end: Some(Expiration::AtTime(now.plus_seconds(200))),
+                ..start_auction_msg("scheduled")
+            }),
+            &[],
         )
         .unwrap();
+        let auction_id = Uint64::from(1u64);
+
+        let query_status = |app: &App| {
+            app.wrap()
+                .query_wasm_smart::<crate::msg::QueryAuctionStatusResponse>(
+                    auction.clone(),
+                    &crate::msg::QueryMsg::QueryGetAuctionStatus { auction_id },
+                )
+                .unwrap()
+        };
+
+        let status = query_status(&app);
+        assert_eq!(status.phase, AuctionPhase::Pending);
+        assert_eq!(status.time_remaining, Some(Duration::Time(100)));
 
         let err = bid(
             &mut app,
             auction.clone(),
+            auction_id.u64(),
             bidder1.clone(),
-            Uint128::from(20u128),
+            Uint128::new(10),
         )
         .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bidding has not started for this auction"));
 
-        // assert can only bid once
-        let err_str = format!("{:?}", err);
-        assert!(err_str.contains("Participant has already placed a bid for this auction"));
+        app.update_block(|block| block.time = block.time.plus_seconds(150));
+        let status = query_status(&app);
+        assert_eq!(status.phase, AuctionPhase::Bidding);
+        assert_eq!(status.time_remaining, Some(Duration::Time(50)));
 
-        let bids: Vec<Bid> = app
-            .wrap()
-            .query_wasm_smart::<QueryBidsForBidderResponse>(
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder1.clone(),
+            Uint128::new(10),
+        )
+        .unwrap();
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            bidder2.clone(),
+            Uint128::new(20),
+        )
+        .unwrap();
+
+        // Only the owner or auctioneer may close the auction before it ends
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
                 auction.clone(),
-                &crate::msg::QueryMsg::QueryGetBidsForBidder {
-                    bidder: bidder1.clone().into(),
-                    start_after: None,
-                    limit: None,
-                },
+                &ExecuteMsg::ExecuteCloseAuction { auction_id },
+                &[],
             )
-            .unwrap()
-            .bids;
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
 
-        assert_eq!(bids.len(), 1);
-        assert_eq!(bids[0].auction_id, first_auction_id);
-        assert_eq!(bids[0].bidder, bidder1);
+        app.update_block(|block| block.time = block.time.plus_seconds(50));
+        let status = query_status(&app);
+        assert_eq!(status.phase, AuctionPhase::Ended);
+        assert_eq!(status.time_remaining, None);
 
-        close_auction(&mut app, auction.clone());
+        let err = bid(
+            &mut app,
+            auction.clone(),
+            auction_id.u64(),
+            Addr::unchecked("bidder3"),
+            Uint128::new(30),
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", err).contains("Bidding is closed for this auction"));
 
-        let new_auction_id = start_auction(&mut app, auction.clone());
+        // Anyone may close the auction after it ends
+        app.execute_contract(
+            bidder1.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteCloseAuction { auction_id },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_status(&app).phase, AuctionPhase::Closed);
+        // bidder1 also still holds the funds minted for their rejected early bid
+        assert_eq!(balance(&app, &bidder1), Uint128::new(20));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(10));
+    }
 
-        // bid again
-        bid(
+    #[test]
+    fn test_sealed_bidding_window() {
+        let mut app = App::default();
+        let auction = instantiate_auction(&mut app);
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+
+        let height = app.block_info().height;
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &ExecuteMsg::ExecuteStartAuction(StartAuctionMsg {
+                sealed: Some(true),
+                end: Some(Expiration::AtHeight(height + 10)),
+                reveal_end: Some(Expiration::AtHeight(height + 20)),
+                on_insufficient_bids: Some(InsufficientBidsPolicy::NoSale),
+                ..start_auction_msg("sealed")
+            }),
+            &[],
+        )
+        .unwrap();
+
+        commit_bid(
             &mut app,
             auction.clone(),
+            1,
             bidder1.clone(),
-            Uint128::from(20u128),
+            Uint128::new(10),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap();
+        commit_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder2.clone(),
+            Uint128::new(20),
+            Uint128::new(20),
+            "b",
         )
         .unwrap();
 
-        let bids: Vec<Bid> = app
+        // The reveal phase starts on its own once bidding ends
+        app.update_block(|block| block.height += 10);
+        let status = app
             .wrap()
-            .query_wasm_smart::<QueryBidsForBidderResponse>(
+            .query_wasm_smart::<crate::msg::QueryAuctionStatusResponse>(
                 auction.clone(),
-                &crate::msg::QueryMsg::QueryGetBidsForBidder {
-                    bidder: bidder1.clone().into(),
-                    start_after: None,
-                    limit: None,
+                &crate::msg::QueryMsg::QueryGetAuctionStatus {
+                    auction_id: Uint64::from(1u64),
                 },
             )
-            .unwrap()
-            .bids;
-
-        assert_eq!(bids.len(), 2);
-
-        assert_eq!(bids[0].auction_id, first_auction_id);
-        assert_eq!(bids[0].bidder, bidder1);
-
-        assert_eq!(bids[1].auction_id, new_auction_id);
-        assert_eq!(bids[1].bidder, bidder1);
+            .unwrap();
+        assert_eq!(status.phase, AuctionPhase::Reveal);
+        assert_eq!(status.time_remaining, Some(Duration::Height(10)));
 
-        // for bid in bids.iter() {
-        //     assert_eq!(bid.auction_id, auction_id);
-        //     assert_eq!(bid.bidder, bidder1);
-        // }
-    }
+        reveal_bid(
+            &mut app,
+            auction.clone(),
+            1,
+            bidder1.clone(),
+            Uint128::new(10),
+            "a",
+        )
+        .unwrap();
 
-    #[test]
-    fn test_auction_with_one_bid() {
-        let mut app = App::default();
-        let code_id = app.store_code(auction_contract());
-        let auction = app
-            .instantiate_contract(
-                code_id,
+        // Not even the owner can close the auction while bids can still be revealed
+        let err = app
+            .execute_contract(
                 Addr::unchecked(ADMIN),
-                &crate::msg::InstantiateMsg {},
+                auction.clone(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
+                },
                 &[],
-                "coin",
-                None,
             )
-            .unwrap();
-
-        let auction_id = start_auction(&mut app, auction.clone());
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::RevealPhaseOpen {}.to_string()
+        );
 
-        // Place bid
-        bid(
+        reveal_bid(
             &mut app,
             auction.clone(),
-            Addr::unchecked("bidder1"),
-            Uint128::from(100u128),
-        );
-
-        // Close auction
-        close_auction(&mut app, auction.clone());
+            1,
+            bidder2.clone(),
+            Uint128::new(20),
+            "b",
+        )
+        .unwrap();
 
-        let winner_res = app
-            .wrap()
-            .query_wasm_smart::<crate::msg::QueryMsg>(
+        // Closing is left to the owner until the reveal phase is over
+        let err = app
+            .execute_contract(
+                bidder1.clone(),
                 auction.clone(),
-                &crate::msg::QueryMsg::QueryGetAuctionWinner {
-                    auction_id: auction_id.into(),
+                &ExecuteMsg::ExecuteCloseAuction {
+                    auction_id: Uint64::new(1),
                 },
+                &[],
             )
             .unwrap_err();
+        assert!(format!("{:?}", err).contains("Unauthorized"));
 
-        assert_eq!(
-            winner_res,
-            StdError::generic_err("Querier contract error: Generic error: Auction has only one bid, cannot determine winner")
-        );
+        app.update_block(|block| block.height += 10);
+        app.execute_contract(
+            bidder1.clone(),
+            auction.clone(),
+            &ExecuteMsg::ExecuteCloseAuction {
+                auction_id: Uint64::new(1),
+            },
+            &[],
+        )
+        .unwrap();
     }
 
     #[test]
-    fn test_find_winner() {
+    fn test_migrate() {
         let mut app = App::default();
         let code_id = app.store_code(auction_contract());
         let auction = app
             .instantiate_contract(
                 code_id,
                 Addr::unchecked(ADMIN),
-                &crate::msg::InstantiateMsg {},
+                &crate::msg::InstantiateMsg {
+                    owner: None,
+                    fee: None,
+                },
                 &[],
-                "coin",
-                None,
+                "auction",
+                Some(ADMIN.to_string()),
             )
             .unwrap();
-
         let auction_id = start_auction(&mut app, auction.clone());
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        bid(
+            &mut app,
+            auction.clone(),
+            auction_id,
+            bidder1.clone(),
+            Uint128::new(100),
+        )
+        .unwrap();
 
-        let mut bids: Vec<Bid> = Vec::new();
-        let num_bids = rand::thread_rng().gen_range(2..1000);
-        for i in 1..=num_bids {
-            let bidder_name = format!("bidder{}", i);
-            let bid_amount = Uint128::from(u128::from(
-                u32::try_from(rand::thread_rng().gen_range(1..1000)).unwrap(),
-            ));
-            bid(
-                &mut app,
-                auction.clone(),
-                Addr::unchecked(&bidder_name),
-                bid_amount,
-            );
-            bids.push(Bid {
-                auction_id,
-                amount: bid_amount,
-                bidder: Addr::unchecked(&bidder_name),
-                timestamp: Timestamp::from_seconds(0),
-            });
-        }
+        // Migrating from the current version keeps open auctions and their bids
+        app.migrate_contract(
+            Addr::unchecked(ADMIN),
+            auction.clone(),
+            &MigrateMsg {
+                owner: None,
+                bid_denom: None,
+            },
+            code_id,
+        )
+        .unwrap();
 
-        // Query bids for auction
-        let queried_bids: Vec<Bid> = app
+        let res: QueryBidsForBidderResponse = app
             .wrap()
             .query_wasm_smart(
                 auction.clone(),
-                &crate::msg::QueryMsg::QueryGetBidsForAuction {
-                    auction_id: auction_id.into(),
+                &crate::msg::QueryMsg::QueryGetBidsForBidder {
+                    bidder: bidder1.to_string(),
                     start_after: None,
                     limit: None,
                 },
             )
             .unwrap();
+        assert_eq!(res.bids.len(), 1);
 
-        assert_eq!(queried_bids.len(), bids.len());
-
-        // Close auction
-        app.execute_contract(
-            Addr::unchecked(ADMIN),
+        bid(
+            &mut app,
             auction.clone(),
-            &ExecuteMsg::ExecuteCloseAuction {},
-            &[],
+            auction_id,
+            bidder2.clone(),
+            Uint128::new(70),
         )
         .unwrap();
+        close_auction(&mut app, auction.clone(), auction_id);
+        assert_eq!(balance(&app, &bidder1), Uint128::new(30));
+        assert_eq!(balance(&app, &bidder2), Uint128::new(70));
+    }
 
-        // Query winner for auction
-        let winner: Winner = app
-            .wrap()
-            .query_wasm_smart::<crate::msg::QueryAuctionWinnerResponse>(
-                auction,
-                &crate::msg::QueryMsg::QueryGetAuctionWinner {
-                    auction_id: auction_id.into(),
-                },
-            )
-            .unwrap()
-            .winner;
+    #[test]
+    fn test_migrate_version_checks() {
+        let mut deps = mock_dependencies();
+        let msg = MigrateMsg {
+            owner: None,
+            bid_denom: None,
+        };
 
-        let winning_bid = bids.iter().max_by(|a, b| a.amount.cmp(&b.amount)).unwrap();
-        assert_eq!(winner.bidder, winning_bid.bidder);
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "1.0.1").unwrap();
+        let err = super::migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::WrongContract {
+                expected: CONTRACT_NAME.to_string(),
+                found: "crates.io:cw20-base".to_string(),
+            }
+            .to_string()
+        );
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = super::migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::MigrationDowngrade {
+                stored: "99.0.0".to_string(),
+                new: CONTRACT_VERSION.to_string(),
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_migrate_from_v0_1() {
+        let mut deps = mock_dependencies();
+        let bidder1 = Addr::unchecked("bidder1");
+        let bidder2 = Addr::unchecked("bidder2");
+        let legacy_bid = |auction_id: u64, bidder: &Addr, amount: u128, seconds: u64| v0_1::Bid {
+            auction_id,
+            amount: Uint128::new(amount),
+            bidder: bidder.clone(),
+            timestamp: Timestamp::from_seconds(seconds),
+        };
+
+        // What 0.1.0 left behind: a closed auction and the open auction after it
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        CURRENT_AUCTION_ID.save(deps.as_mut().storage, &2).unwrap();
+        let legacy_auctions = [
+            v0_1::Auction {
+                id: 1,
+                name: "closed".to_string(),
+                in_progress: false,
+                max_participants: Uint64::new(2),
+                sorted_bids: vec![
+                    legacy_bid(1, &bidder1, 100, 1),
+                    legacy_bid(1, &bidder2, 50, 2),
+                ],
+                winner: Some(v0_1::Winner {
+                    auction_id: 1,
+                    amount_owed: Uint128::new(50),
+                    bidder: bidder1.clone(),
+                }),
+            },
+            v0_1::Auction {
+                id: 2,
+                name: "open".to_string(),
+                in_progress: true,
+                max_participants: Uint64::new(2),
+                sorted_bids: vec![
+                    legacy_bid(2, &bidder2, 80, 4),
+                    legacy_bid(2, &bidder1, 60, 3),
+                ],
+                winner: None,
+            },
+        ];
+        for legacy in legacy_auctions {
+            for bid in legacy.sorted_bids.iter() {
+                v0_1::BIDDERS_TO_BIDS
+                    .save(deps.as_mut().storage, (&bid.bidder, legacy.id), bid)
+                    .unwrap();
+            }
+            v0_1::AUCTIONS
+                .save(deps.as_mut().storage, legacy.id, &legacy)
+                .unwrap();
+        }
+
+        // 0.1.0 had no owner, so migrating from it needs one
+        let err = super::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                owner: None,
+                bid_denom: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::MissingLegacyConfig {
+                version: "0.1.0".to_string(),
+            }
+            .to_string()
+        );
+
+        super::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                owner: Some(ADMIN.to_string()),
+                bid_denom: Some(UncheckedDenom::Native(DENOM.to_string())),
+            },
+        )
+        .unwrap();
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        assert_eq!(OWNER.load(deps.as_ref().storage).unwrap(), ADMIN);
+
+        for (auction_id, phase, winner, amount_owed) in [
+            (1, AuctionPhase::Settled, &bidder1, 50),
+            (2, AuctionPhase::Closed, &bidder2, 60),
+        ] {
+            let auction = AUCTIONS.load(deps.as_ref().storage, auction_id).unwrap();
+            assert_eq!(auction.phase, phase);
+            assert_eq!(auction.num_bids, 2);
+            assert_eq!(
+                auction.winners,
+                vec![Winner {
+                    auction_id,
+                    amount_owed: Uint128::new(amount_owed),
+                    bidder: winner.clone(),
+                    bundle: None,
+                }]
+            );
+        }
+
+        // Nothing was escrowed for the open auction, so settling it pays nothing
+        let res = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            ExecuteMsg::ExecuteSettleAuction {
+                auction_id: Uint64::new(2),
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let auction = AUCTIONS.load(deps.as_ref().storage, 2).unwrap();
+        assert_eq!(auction.phase, AuctionPhase::Settled);
+
+        // Bids are ordered by when they were placed
+        let bid = bids().load(deps.as_ref().storage, (2, &bidder1)).unwrap();
+        assert_eq!(bid.sequence, 0);
+        assert!(v0_1::BIDDERS_TO_BIDS
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+
+        // The owner can start auctions after the migrated ones
+        let res = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            ExecuteMsg::ExecuteStartAuction(start_auction_msg("after migration")),
+        )
+        .unwrap();
+        let started = res
+            .events
+            .iter()
+            .find(|ev| ev.ty == events::AUCTION_STARTED)
+            .unwrap();
+        assert!(started
+            .attributes
+            .iter()
+            .any(|attr| attr.key == events::ATTR_AUCTION_ID && attr.value == "3"));
     }
 }
 
@@ -1,4 +1,5 @@
 use cosmwasm_std::{StdError, Uint128, Uint64};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,21 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
+    #[error("Cannot migrate from contract {found}, expected {expected}")]
+    WrongContract { expected: String, found: String },
+
+    #[error("Cannot migrate from version {stored} to the older version {new}")]
+    MigrationDowngrade { stored: String, new: String },
+
+    #[error("Migrating from version {version} requires an owner and a bid denom")]
+    MissingLegacyConfig { version: String },
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -15,8 +31,98 @@ pub enum ContractError {
     #[error("Auction is not in progress")]
     AuctionNotInProgress {},
 
-    #[error("There must be at least 2 participants")]
-    TooFewParticipants {},
+    #[error("Auction was cancelled")]
+    AuctionCancelled {},
+
+    #[error("Auction has not closed")]
+    AuctionNotClosed {},
+
+    #[error("Auction has already been settled")]
+    AuctionAlreadySettled {},
+
+    #[error(
+        "Proceeds must go to between 1 and {max} distinct beneficiaries with positive weights"
+    )]
+    InvalidBeneficiaries { max: usize },
+
+    #[error("Reverse auctions pay their winners and cannot name beneficiaries")]
+    BeneficiariesNotAllowed {},
+
+    #[error("A lot can only be sold in a forward auction of a single item")]
+    InvalidLot {},
+
+    #[error("Protocol fee must be at most {max} basis points")]
+    InvalidFee { max: u16 },
+
+    #[error("Bid bond must be greater than 0")]
+    InvalidBond {},
+
+    #[error("Bids in this auction must carry a bond of {bond}")]
+    MissingBond { bond: Uint128 },
+
+    #[error("Attached funds ({deposit}) do not cover the bond of {bond}")]
+    InsufficientBond { bond: Uint128, deposit: Uint128 },
+
+    #[error("Auction does not take bid bonds")]
+    NoBond {},
+
+    #[error("Forward auction winners pay from escrow and cannot default")]
+    WinnerCannotDefault {},
+
+    #[error("Bidder did not win this auction")]
+    NotAWinner {},
+
+    #[error("There must be at least {needed} participants")]
+    TooFewParticipants { needed: u64 },
+
+    #[error("Quantity must be at least 1 and below the maximum number of participants")]
+    InvalidQuantity {},
+
+    #[error(
+        "Combinatorial auctions must be open forward auctions with no reserve, sell one of each of at most {} items and take at most {} bidders",
+        crate::combinatorial::MAX_ITEMS,
+        crate::combinatorial::MAX_BIDDERS
+    )]
+    InvalidCombinatorialAuction {},
+
+    #[error("Auction only accepts bids on bundles of items")]
+    IsCombinatorial {},
+
+    #[error("Auction does not sell bundles of items")]
+    NotCombinatorial {},
+
+    #[error("Bundles must name distinct items sold in the auction")]
+    InvalidBundle {},
+
+    #[error("A bid may name at most {max} bundles")]
+    TooManyBundles { max: usize },
+
+    #[error("Auction has no secret reserve")]
+    NoSecretReserve {},
+
+    #[error("Reserve has already been revealed")]
+    ReserveAlreadyRevealed {},
+
+    #[error("Revealed reserve does not match its commitment")]
+    ReserveMismatch {},
+
+    #[error("Auction does not break ties by lottery")]
+    NoLottery {},
+
+    #[error("Lottery seed has already been revealed")]
+    LotterySeedAlreadyRevealed {},
+
+    #[error("Revealed lottery seed does not match its commitment")]
+    LotterySeedMismatch {},
+
+    #[error("Lottery seed must be revealed before the auction closes")]
+    LotterySeedNotRevealed {},
+
+    #[error("Bid asks for more than the auction's budget allows, at most {max_ask}")]
+    BidAboveBudget { max_ask: Uint128 },
+
+    #[error("Auctions can only be started through trusted token contracts")]
+    UntrustedContract {},
 
     #[error("Bid amount must be greater than 0")]
     BidAmountTooLow {},
@@ -35,4 +141,67 @@ pub enum ContractError {
 
     #[error("Auction not found")]
     AuctionNotFound { auction_id: u64 },
+
+    #[error("Bids for this auction are sealed, submit a commitment instead")]
+    AuctionIsSealed {},
+
+    #[error("Auction does not accept sealed bids")]
+    AuctionNotSealed {},
+
+    #[error("Bidding has not started for this auction")]
+    BiddingNotStarted {},
+
+    #[error("Bidding is closed for this auction")]
+    BiddingClosed {},
+
+    #[error("Auction is not in its reveal phase")]
+    NotInRevealPhase {},
+
+    #[error("Bids can still be revealed until the reveal phase ends")]
+    RevealPhaseOpen {},
+
+    #[error("No bid was found for this bidder")]
+    BidNotFound {},
+
+    #[error("Bids can no longer be withdrawn from this auction")]
+    WithdrawalsLocked {},
+
+    #[error("Bids in this auction cannot be amended")]
+    AmendmentsDisabled {},
+
+    #[error("No bid commitment was found for this bidder")]
+    NoCommitmentFound {},
+
+    #[error("Revealed bid does not match the commitment")]
+    CommitmentMismatch {},
+
+    #[error("Bid has already been revealed")]
+    BidAlreadyRevealed {},
+
+    #[error("Attached funds ({deposit}) do not match the bid amount ({bid_amount})")]
+    BidFundsMismatch {
+        bid_amount: Uint128,
+        deposit: Uint128,
+    },
+
+    #[error("Auction schedule is invalid")]
+    InvalidSchedule {},
+
+    #[error("Bidder is not allowed to bid in this auction")]
+    BidderNotAllowed {},
+
+    #[error("Auction does not restrict who may bid")]
+    AuctionNotRestricted {},
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Bid was paid in a token this auction does not accept")]
+    WrongBidToken {},
+
+    #[error("Deposit ({deposit}) does not cover the revealed bid ({bid_amount})")]
+    InsufficientDeposit {
+        bid_amount: Uint128,
+        deposit: Uint128,
+    },
 }
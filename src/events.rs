@@ -0,0 +1,349 @@
+//! The events the contract emits, so indexers can follow auctions without
+//! polling state.
+//!
+//! Each event is emitted with the `wasm-` prefix, e.g. `wasm-vcg_auction_start`.
+//! Events about an auction carry the `auction_id` it is about, while those about
+//! the contract's configuration carry none. The attributes of each event are
+//! listed on its type below.
+
+use cosmwasm_std::{Addr, Event, Uint128};
+use cw20::Denom;
+
+use crate::state::{Auction, AuctionDirection, Bid, FeeConfig, Lot, Winner};
+
+/// An auction was started.
+/// `auction_id`, `creator`, `direction`, `sealed`, `quantity`, `bid_denom`.
+pub const AUCTION_STARTED: &str = "vcg_auction_start";
+
+/// A bid, bundle bid or sealed commitment was placed.
+/// `auction_id`, `bidder`, `participant_count`, and `amount` unless sealed.
+pub const BID_PLACED: &str = "vcg_auction_bid";
+
+/// A sealed bid was revealed.
+/// `auction_id`, `bidder`, `amount`, `participant_count`.
+pub const BID_REVEALED: &str = "vcg_auction_reveal_bid";
+
+/// A bid was raised or lowered.
+/// `auction_id`, `bidder`, `amount`, `amendments`.
+pub const BID_UPDATED: &str = "vcg_auction_update_bid";
+
+/// A bid or sealed commitment was withdrawn.
+/// `auction_id`, `bidder`, `participant_count`.
+pub const BID_WITHDRAWN: &str = "vcg_auction_withdraw_bid";
+
+/// A sealed auction stopped taking commitments and started its reveal phase.
+/// `auction_id`.
+pub const REVEAL_STARTED: &str = "vcg_auction_start_reveal";
+
+/// A secret reserve was revealed.
+/// `auction_id`, `reserve`.
+pub const RESERVE_REVEALED: &str = "vcg_auction_reveal_reserve";
+
+/// The seed of a tie-break lottery was revealed.
+/// `auction_id`, `seed`.
+pub const LOTTERY_SEED_REVEALED: &str = "vcg_auction_reveal_lottery_seed";
+
+/// An auction was closed. Followed by one `WINNER_DETERMINED` event per winner.
+/// `auction_id`, `participant_count`, `winner_count`, `reserve_binding`,
+/// `protocol_fee`, and `clearing_price` unless the auction was combinatorial or
+/// had no winner.
+pub const AUCTION_CLOSED: &str = "vcg_auction_close";
+
+/// An auction was cancelled. Followed by a `SETTLEMENT` event per refund.
+/// `auction_id`, `participant_count`, `reason`.
+pub const AUCTION_CANCELLED: &str = "vcg_auction_cancel";
+
+/// A closed auction's proceeds were paid out. Followed by a `SETTLEMENT` event
+/// per payment.
+/// `auction_id`, `proceeds`, the winners' payments before the protocol fee.
+pub const AUCTION_SETTLED: &str = "vcg_auction_settle";
+
+/// An escrowed lot left the contract, to the winner or back to the creator.
+/// `auction_id`, `recipient`, and `nft_contract` and `token_id` for NFTs, or
+/// `lot_denom` and `amount` for tokens.
+pub const LOT_TRANSFERRED: &str = "vcg_auction_lot_transfer";
+
+/// A bidder won an auction.
+/// `auction_id`, `bidder`, `amount_owed`, and `bundle` in combinatorial auctions.
+pub const WINNER_DETERMINED: &str = "vcg_auction_winner";
+
+/// A reverse auction winner was declared in default. Followed by a `SETTLEMENT`
+/// event for the slashed bond and one for the budget returned to the buyer.
+/// `auction_id`, `bidder`, `amount_owed`.
+pub const WINNER_DEFAULTED: &str = "vcg_auction_default";
+
+/// Escrowed funds were paid out of a closed, settled or cancelled auction.
+/// `auction_id`, `recipient`, `amount`, `kind`.
+pub const SETTLEMENT: &str = "vcg_auction_settlement";
+
+/// Addresses were added to or removed from an auction's allowlist.
+/// `auction_id`, and `added` and `removed` as comma separated addresses unless empty.
+pub const ALLOWED_BIDDERS_UPDATED: &str = "vcg_auction_update_allowed_bidders";
+
+/// Token contracts were added to or removed from the trusted contracts.
+/// `added` and `removed` as comma separated addresses unless empty.
+pub const TRUSTED_CONTRACTS_UPDATED: &str = "vcg_auction_update_trusted_contracts";
+
+/// The protocol fee was set or removed.
+/// `fee_bps` and `fee_collector`, unless the fee was removed.
+pub const FEE_CONFIG_UPDATED: &str = "vcg_auction_update_fee";
+
+/// The owner proposed a new owner.
+/// `owner`, `pending_owner`.
+pub const OWNERSHIP_PROPOSED: &str = "vcg_auction_propose_owner";
+
+/// The proposed owner accepted ownership.
+/// `owner`.
+pub const OWNERSHIP_ACCEPTED: &str = "vcg_auction_accept_owner";
+
+/// The owner gave up ownership.
+/// `previous_owner`.
+pub const OWNERSHIP_RENOUNCED: &str = "vcg_auction_renounce_owner";
+
+/// The contract was migrated.
+/// `from_version`, `to_version`.
+pub const CONTRACT_MIGRATED: &str = "vcg_auction_migrate";
+
+pub const ATTR_AUCTION_ID: &str = "auction_id";
+pub const ATTR_CREATOR: &str = "creator";
+pub const ATTR_DIRECTION: &str = "direction";
+pub const ATTR_SEALED: &str = "sealed";
+pub const ATTR_QUANTITY: &str = "quantity";
+pub const ATTR_BID_DENOM: &str = "bid_denom";
+pub const ATTR_BIDDER: &str = "bidder";
+pub const ATTR_AMOUNT: &str = "amount";
+pub const ATTR_AMENDMENTS: &str = "amendments";
+pub const ATTR_PARTICIPANT_COUNT: &str = "participant_count";
+pub const ATTR_RESERVE: &str = "reserve";
+pub const ATTR_SEED: &str = "seed";
+pub const ATTR_WINNER_COUNT: &str = "winner_count";
+pub const ATTR_RESERVE_BINDING: &str = "reserve_binding";
+pub const ATTR_CLEARING_PRICE: &str = "clearing_price";
+pub const ATTR_AMOUNT_OWED: &str = "amount_owed";
+pub const ATTR_BUNDLE: &str = "bundle";
+pub const ATTR_REASON: &str = "reason";
+pub const ATTR_PROCEEDS: &str = "proceeds";
+pub const ATTR_PROTOCOL_FEE: &str = "protocol_fee";
+pub const ATTR_RECIPIENT: &str = "recipient";
+pub const ATTR_KIND: &str = "kind";
+pub const ATTR_NFT_CONTRACT: &str = "nft_contract";
+pub const ATTR_TOKEN_ID: &str = "token_id";
+pub const ATTR_LOT_DENOM: &str = "lot_denom";
+pub const ATTR_ADDED: &str = "added";
+pub const ATTR_REMOVED: &str = "removed";
+pub const ATTR_FEE_BPS: &str = "fee_bps";
+pub const ATTR_FEE_COLLECTOR: &str = "fee_collector";
+pub const ATTR_OWNER: &str = "owner";
+pub const ATTR_PENDING_OWNER: &str = "pending_owner";
+pub const ATTR_PREVIOUS_OWNER: &str = "previous_owner";
+pub const ATTR_FROM_VERSION: &str = "from_version";
+pub const ATTR_TO_VERSION: &str = "to_version";
+
+/// `kind` of a settlement returning escrow to the bidder or buyer who paid it in.
+pub const SETTLEMENT_REFUND: &str = "refund";
+/// `kind` of a settlement paying proceeds to a beneficiary, or to a reverse auction
+/// winner out of the budget.
+pub const SETTLEMENT_PAYMENT: &str = "payment";
+/// `kind` of a settlement paying the protocol fee to the fee collector.
+pub const SETTLEMENT_FEE: &str = "fee";
+/// `kind` of a settlement paying a slashed bond to the auction's slash recipient.
+pub const SETTLEMENT_SLASH: &str = "slash";
+
+fn auction_event(ty: &str, auction_id: u64) -> Event {
+    Event::new(ty).add_attribute(ATTR_AUCTION_ID, auction_id.to_string())
+}
+
+/// Adds `added` and `removed` as comma separated lists, leaving out empty ones.
+fn add_address_changes(mut event: Event, added: &[Addr], removed: &[Addr]) -> Event {
+    for (key, addresses) in [(ATTR_ADDED, added), (ATTR_REMOVED, removed)] {
+        if !addresses.is_empty() {
+            let addresses: Vec<&str> = addresses.iter().map(|address| address.as_str()).collect();
+            event = event.add_attribute(key, addresses.join(","));
+        }
+    }
+    event
+}
+
+/// The native denom, or the address of the CW20 token.
+fn denom_name(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => denom.clone(),
+        Denom::Cw20(address) => address.to_string(),
+    }
+}
+
+pub fn auction_started(auction: &Auction) -> Event {
+    let direction = match auction.direction {
+        AuctionDirection::Forward => "forward",
+        AuctionDirection::Reverse => "reverse",
+    };
+    auction_event(AUCTION_STARTED, auction.id)
+        .add_attribute(ATTR_CREATOR, auction.creator.to_string())
+        .add_attribute(ATTR_DIRECTION, direction)
+        .add_attribute(ATTR_SEALED, auction.sealed.to_string())
+        .add_attribute(ATTR_QUANTITY, auction.quantity.to_string())
+        .add_attribute(ATTR_BID_DENOM, denom_name(&auction.bid_denom))
+}
+
+pub fn bid_placed(auction: &Auction, bidder: &Addr, amount: Option<Uint128>) -> Event {
+    let event = auction_event(BID_PLACED, auction.id)
+        .add_attribute(ATTR_BIDDER, bidder.to_string())
+        .add_attribute(
+            ATTR_PARTICIPANT_COUNT,
+            auction.participant_count().to_string(),
+        );
+    match amount {
+        Some(amount) => event.add_attribute(ATTR_AMOUNT, amount.to_string()),
+        None => event,
+    }
+}
+
+pub fn bid_revealed(auction: &Auction, bid: &Bid) -> Event {
+    auction_event(BID_REVEALED, auction.id)
+        .add_attribute(ATTR_BIDDER, bid.bidder.to_string())
+        .add_attribute(ATTR_AMOUNT, bid.amount.to_string())
+        .add_attribute(
+            ATTR_PARTICIPANT_COUNT,
+            auction.participant_count().to_string(),
+        )
+}
+
+pub fn bid_updated(bid: &Bid) -> Event {
+    auction_event(BID_UPDATED, bid.auction_id)
+        .add_attribute(ATTR_BIDDER, bid.bidder.to_string())
+        .add_attribute(ATTR_AMOUNT, bid.amount.to_string())
+        .add_attribute(ATTR_AMENDMENTS, bid.amendments.to_string())
+}
+
+pub fn bid_withdrawn(auction: &Auction, bidder: &Addr) -> Event {
+    auction_event(BID_WITHDRAWN, auction.id)
+        .add_attribute(ATTR_BIDDER, bidder.to_string())
+        .add_attribute(
+            ATTR_PARTICIPANT_COUNT,
+            auction.participant_count().to_string(),
+        )
+}
+
+pub fn reveal_started(auction_id: u64) -> Event {
+    auction_event(REVEAL_STARTED, auction_id)
+}
+
+pub fn reserve_revealed(auction_id: u64, reserve: Uint128) -> Event {
+    auction_event(RESERVE_REVEALED, auction_id).add_attribute(ATTR_RESERVE, reserve.to_string())
+}
+
+pub fn lottery_seed_revealed(auction_id: u64, seed: &str) -> Event {
+    auction_event(LOTTERY_SEED_REVEALED, auction_id).add_attribute(ATTR_SEED, seed)
+}
+
+pub fn auction_closed(auction: &Auction) -> Event {
+    let event = auction_event(AUCTION_CLOSED, auction.id)
+        .add_attribute(
+            ATTR_PARTICIPANT_COUNT,
+            auction.participant_count().to_string(),
+        )
+        .add_attribute(ATTR_WINNER_COUNT, auction.winners.len().to_string())
+        .add_attribute(ATTR_RESERVE_BINDING, auction.reserve_binding.to_string())
+        .add_attribute(ATTR_PROTOCOL_FEE, auction.protocol_fee.to_string());
+    // Every winner of a single or multi-unit auction owes the same price
+    match auction.winners.first() {
+        Some(winner) if !auction.is_combinatorial() => {
+            event.add_attribute(ATTR_CLEARING_PRICE, winner.amount_owed.to_string())
+        }
+        _ => event,
+    }
+}
+
+pub fn auction_cancelled(auction: &Auction, reason: &str) -> Event {
+    auction_event(AUCTION_CANCELLED, auction.id)
+        .add_attribute(
+            ATTR_PARTICIPANT_COUNT,
+            auction.participant_count().to_string(),
+        )
+        .add_attribute(ATTR_REASON, reason)
+}
+
+pub fn auction_settled(auction_id: u64, proceeds: Uint128) -> Event {
+    auction_event(AUCTION_SETTLED, auction_id).add_attribute(ATTR_PROCEEDS, proceeds.to_string())
+}
+
+pub fn winner_determined(winner: &Winner) -> Event {
+    let event = auction_event(WINNER_DETERMINED, winner.auction_id)
+        .add_attribute(ATTR_BIDDER, winner.bidder.to_string())
+        .add_attribute(ATTR_AMOUNT_OWED, winner.amount_owed.to_string());
+    match &winner.bundle {
+        Some(bundle) => {
+            let items: Vec<String> = bundle.iter().map(|item| item.to_string()).collect();
+            event.add_attribute(ATTR_BUNDLE, items.join(","))
+        }
+        None => event,
+    }
+}
+
+pub fn winner_defaulted(winner: &Winner) -> Event {
+    auction_event(WINNER_DEFAULTED, winner.auction_id)
+        .add_attribute(ATTR_BIDDER, winner.bidder.to_string())
+        .add_attribute(ATTR_AMOUNT_OWED, winner.amount_owed.to_string())
+}
+
+pub fn settlement(auction_id: u64, recipient: &Addr, amount: Uint128, kind: &str) -> Event {
+    auction_event(SETTLEMENT, auction_id)
+        .add_attribute(ATTR_RECIPIENT, recipient.to_string())
+        .add_attribute(ATTR_AMOUNT, amount.to_string())
+        .add_attribute(ATTR_KIND, kind)
+}
+
+pub fn lot_transferred(auction_id: u64, lot: &Lot, recipient: &Addr) -> Event {
+    let event = auction_event(LOT_TRANSFERRED, auction_id)
+        .add_attribute(ATTR_RECIPIENT, recipient.to_string());
+    match lot {
+        Lot::Nft { contract, token_id } => event
+            .add_attribute(ATTR_NFT_CONTRACT, contract.to_string())
+            .add_attribute(ATTR_TOKEN_ID, token_id),
+        Lot::Tokens { denom, amount } => event
+            .add_attribute(ATTR_LOT_DENOM, denom_name(denom))
+            .add_attribute(ATTR_AMOUNT, amount.to_string()),
+    }
+}
+
+pub fn allowed_bidders_updated(auction_id: u64, added: &[Addr], removed: &[Addr]) -> Event {
+    add_address_changes(
+        auction_event(ALLOWED_BIDDERS_UPDATED, auction_id),
+        added,
+        removed,
+    )
+}
+
+pub fn trusted_contracts_updated(added: &[Addr], removed: &[Addr]) -> Event {
+    add_address_changes(Event::new(TRUSTED_CONTRACTS_UPDATED), added, removed)
+}
+
+pub fn fee_config_updated(fee: Option<&FeeConfig>) -> Event {
+    let event = Event::new(FEE_CONFIG_UPDATED);
+    match fee {
+        Some(fee) => event
+            .add_attribute(ATTR_FEE_BPS, fee.bps.to_string())
+            .add_attribute(ATTR_FEE_COLLECTOR, fee.collector.to_string()),
+        None => event,
+    }
+}
+
+pub fn ownership_proposed(owner: &Addr, pending_owner: &Addr) -> Event {
+    Event::new(OWNERSHIP_PROPOSED)
+        .add_attribute(ATTR_OWNER, owner.to_string())
+        .add_attribute(ATTR_PENDING_OWNER, pending_owner.to_string())
+}
+
+pub fn ownership_accepted(owner: &Addr) -> Event {
+    Event::new(OWNERSHIP_ACCEPTED).add_attribute(ATTR_OWNER, owner.to_string())
+}
+
+pub fn ownership_renounced(previous_owner: &Addr) -> Event {
+    Event::new(OWNERSHIP_RENOUNCED).add_attribute(ATTR_PREVIOUS_OWNER, previous_owner.to_string())
+}
+
+pub fn contract_migrated(from_version: &str, to_version: &str) -> Event {
+    Event::new(CONTRACT_MIGRATED)
+        .add_attribute(ATTR_FROM_VERSION, from_version)
+        .add_attribute(ATTR_TO_VERSION, to_version)
+}
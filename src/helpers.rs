@@ -1,9 +1,17 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{to_binary, Addr, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{
+    coins, to_json_binary, Addr, BankMsg, Coin, CosmosMsg, HexBinary, StdResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Denom};
+use cw721::Cw721ExecuteMsg;
+use cw_utils::PaymentError;
+use sha2::{Digest, Sha256};
 
 use crate::msg::ExecuteMsg;
+use crate::state::Lot;
+use crate::ContractError;
 
 /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -16,7 +24,7 @@ impl CwTemplateContract {
     }
 
     pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
-        let msg = to_binary(&msg.into())?;
+        let msg = to_json_binary(&msg.into())?;
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
             msg,
@@ -25,3 +33,121 @@ impl CwTemplateContract {
         .into())
     }
 }
+
+/// Computes the commitment for a sealed bid: the sha256 hash of the amount as
+/// 16 big-endian bytes, followed by the bidder address and the salt.
+///
+/// Binding the bidder into the hash stops others from copying a commitment
+/// and replaying its reveal.
+pub fn bid_commitment(bidder: &Addr, amount: Uint128, salt: &str) -> HexBinary {
+    let mut hasher = Sha256::new();
+    hasher.update(amount.u128().to_be_bytes());
+    hasher.update(bidder.as_bytes());
+    hasher.update(salt.as_bytes());
+    HexBinary::from(hasher.finalize().as_slice())
+}
+
+/// Computes the commitment for a secret reserve price: the sha256 hash of the
+/// amount as 16 big-endian bytes, followed by the salt.
+pub fn reserve_commitment(amount: Uint128, salt: &str) -> HexBinary {
+    let mut hasher = Sha256::new();
+    hasher.update(amount.u128().to_be_bytes());
+    hasher.update(salt.as_bytes());
+    HexBinary::from(hasher.finalize().as_slice())
+}
+
+/// Computes the commitment for a lottery seed: the sha256 hash of the seed.
+pub fn lottery_commitment(seed: &str) -> HexBinary {
+    HexBinary::from(Sha256::digest(seed.as_bytes()).as_slice())
+}
+
+/// Draws the lottery ticket for the bid with `sequence`: the sha256 hash of
+/// the seed followed by the sequence as 8 big-endian bytes. Among equal bids,
+/// the lowest ticket wins.
+pub fn lottery_ticket(seed: &str, sequence: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_bytes());
+    hasher.update(sequence.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Funds sent along with a message, either natively or through a CW20 `Send`.
+pub enum Payment {
+    Native(Vec<Coin>),
+    Cw20 { token: Addr, amount: Uint128 },
+}
+
+impl Payment {
+    /// Returns the amount paid, as long as all of it was paid in `denom`.
+    pub fn amount_in(&self, denom: &Denom) -> Result<Uint128, ContractError> {
+        match (self, denom) {
+            (Payment::Native(funds), Denom::Native(denom)) => match funds.as_slice() {
+                [] => Err(PaymentError::NoFunds {}.into()),
+                [coin] if &coin.denom == denom => Ok(coin.amount),
+                [_] => Err(ContractError::WrongBidToken {}),
+                _ => Err(PaymentError::MultipleDenoms {}.into()),
+            },
+            (Payment::Cw20 { token, amount }, Denom::Cw20(address)) if token == address => {
+                Ok(*amount)
+            }
+            _ => Err(ContractError::WrongBidToken {}),
+        }
+    }
+
+    /// Returns the denom and amount paid, or `None` if nothing was paid.
+    /// Errors if more than one denom was paid.
+    pub fn single_asset(&self) -> Result<Option<(Denom, Uint128)>, ContractError> {
+        match self {
+            Payment::Native(funds) => match funds.as_slice() {
+                [] => Ok(None),
+                [coin] => Ok(Some((Denom::Native(coin.denom.clone()), coin.amount))),
+                _ => Err(PaymentError::MultipleDenoms {}.into()),
+            },
+            Payment::Cw20 { token, amount } => Ok(Some((Denom::Cw20(token.clone()), *amount))),
+        }
+    }
+
+    /// Errors if anything was paid.
+    pub fn nonpayable(&self) -> Result<(), ContractError> {
+        match self {
+            Payment::Native(funds) if funds.is_empty() => Ok(()),
+            _ => Err(PaymentError::NonPayable {}.into()),
+        }
+    }
+}
+
+/// Builds a message sending `amount` of `denom` from this contract to `recipient`.
+pub fn transfer_msg(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+        Denom::Cw20(address) => WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Builds a message sending an escrowed lot from this contract to `recipient`.
+pub fn lot_transfer_msg(lot: &Lot, recipient: &Addr) -> StdResult<CosmosMsg> {
+    Ok(match lot {
+        Lot::Nft { contract, token_id } => WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        }
+        .into(),
+        Lot::Tokens { denom, amount } => transfer_msg(denom, recipient, *amount)?,
+    })
+}
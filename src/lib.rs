@@ -1,6 +1,9 @@
+pub mod combinatorial;
 pub mod contract;
 mod error;
+pub mod events;
 pub mod helpers;
+pub mod migrations;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,135 @@
+//! Upgrades for state written by earlier versions of the contract.
+
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
+use cw20::Denom;
+
+use crate::state::{
+    bids, Auction, AuctionDirection, AuctionPhase, Bid, InsufficientBidsPolicy, TieBreak, AUCTIONS,
+};
+
+/// The state layout of version 0.1.0, which kept each auction's bids in a list
+/// on the auction and again in `bidders_to_bids`.
+pub mod v0_1 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, Timestamp, Uint128, Uint64};
+    use cw_storage_plus::Map;
+
+    #[cw_serde]
+    pub struct Auction {
+        pub id: u64,
+        pub name: String,
+        pub in_progress: bool,
+        pub max_participants: Uint64,
+        pub sorted_bids: Vec<Bid>,
+        pub winner: Option<Winner>,
+    }
+
+    #[cw_serde]
+    pub struct Bid {
+        pub auction_id: u64,
+        pub amount: Uint128,
+        pub bidder: Addr,
+        pub timestamp: Timestamp,
+    }
+
+    #[cw_serde]
+    pub struct Winner {
+        pub auction_id: u64,
+        pub amount_owed: Uint128,
+        pub bidder: Addr,
+    }
+
+    pub const AUCTIONS: Map<u64, Auction> = Map::new("auctions");
+
+    pub const BIDDERS_TO_BIDS: Map<(&Addr, u64), Bid> = Map::new("bidders_to_bids");
+}
+
+/// Converts the auctions written by 0.1.0 and moves their bids into `bids()`.
+/// 0.1.0 had no owners or bid denoms, so `creator` and `bid_denom` are recorded
+/// on every converted auction.
+///
+/// Each auction gets the winners 0.1.0 would have reported: the highest bidder,
+/// paying the second highest bid. Auctions 0.1.0 closed are marked settled and
+/// the ones it left open are closed. 0.1.0 took bids without escrow, so the
+/// auctions have no beneficiaries and settling a migrated one pays nothing.
+pub fn migrate_auctions_from_v0_1(
+    storage: &mut dyn Storage,
+    creator: &Addr,
+    bid_denom: &Denom,
+) -> StdResult<()> {
+    let legacy_auctions = v0_1::AUCTIONS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (auction_id, legacy) in legacy_auctions {
+        let mut legacy_bids = legacy.sorted_bids;
+        legacy_bids.sort_by_key(|bid| bid.timestamp);
+
+        let mut auction = Auction {
+            id: auction_id,
+            name: legacy.name,
+            phase: if legacy.in_progress {
+                AuctionPhase::Closed
+            } else {
+                AuctionPhase::Settled
+            },
+            direction: AuctionDirection::Forward,
+            budget: Uint128::zero(),
+            sealed: false,
+            max_participants: legacy.max_participants,
+            num_bids: legacy_bids.len() as u64,
+            num_commitments: 0,
+            next_sequence: legacy_bids.len() as u64,
+            // 0.1.0 ranked equal bids by the later timestamp first
+            tie_break: TieBreak::LatestBid,
+            quantity: 1,
+            items: vec![],
+            bid_denom: bid_denom.clone(),
+            creator: creator.clone(),
+            auctioneer: None,
+            restrict_bidders: false,
+            insufficient_bids_policy: InsufficientBidsPolicy::NoSale,
+            reserve: None,
+            reserve_binding: false,
+            bidding_start: None,
+            bidding_end: None,
+            reveal_end: None,
+            withdrawals_locked_after: None,
+            allow_amendments: true,
+            bundle_bids: vec![],
+            winners: vec![],
+            cancel_reason: None,
+            beneficiaries: vec![],
+            fee: None,
+            protocol_fee: Uint128::zero(),
+            lot: None,
+            bond: None,
+        };
+
+        for (sequence, legacy_bid) in legacy_bids.into_iter().enumerate() {
+            let bid = Bid {
+                auction_id,
+                amount: legacy_bid.amount,
+                bidder: legacy_bid.bidder,
+                timestamp: legacy_bid.timestamp,
+                sequence: sequence as u64,
+                deposit: Uint128::zero(),
+                amendments: 0,
+                last_updated: legacy_bid.timestamp,
+            };
+            bids().save(storage, (auction_id, &bid.bidder), &bid)?;
+        }
+
+        auction.winners = auction.determine_winners(storage)?;
+        AUCTIONS.save(storage, auction_id, &auction)?;
+    }
+
+    let legacy_keys = v0_1::BIDDERS_TO_BIDS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (bidder, auction_id) in legacy_keys {
+        v0_1::BIDDERS_TO_BIDS.remove(storage, (&bidder, auction_id));
+    }
+
+    Ok(())
+}
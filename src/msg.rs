@@ -1,48 +1,372 @@
-use crate::state::{Bid, Winner};
+use crate::state::{
+    AuctionDirection, AuctionPhase, Beneficiary, Bid, BidBond, BondStatus, BundleValue, FeeConfig,
+    InsufficientBidsPolicy, Lot, TieBreak, Winner,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128, Uint64};
+use cosmwasm_std::{Addr, Deps, HexBinary, StdResult, Uint128, Uint64};
+use cw20::{Cw20ReceiveMsg, Denom};
+use cw721::Cw721ReceiveMsg;
+use cw_utils::{Duration, Expiration, Scheduled};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// The address allowed to start auctions. Defaults to the instantiator.
+    pub owner: Option<String>,
+    /// The protocol fee charged on cleared auctions. Defaults to none.
+    pub fee: Option<ProtocolFee>,
+}
+
+#[cw_serde]
+pub struct ProtocolFee {
+    /// The fee in basis points of each clearing payment, at most 10000.
+    pub bps: u16,
+    /// The address fees are paid to.
+    pub collector: String,
+}
+
+#[cw_serde]
+pub struct StartAuctionMsg {
+    /// The name of the auction.
+    pub name: String,
+    /// Whether items are sold to the highest bidders or bought from the lowest.
+    /// Defaults to `Forward`. Reverse auctions are started with the buyer's budget
+    /// attached in `bid_denom`, or sent as a CW20 `ReceiveMsg::ExecuteStartAuction`.
+    pub direction: Option<AuctionDirection>,
+    /// The number of participants in the auction.
+    /// Each participant may only bid once.
+    pub max_num_participants: Uint64,
+    /// The number of identical items sold, one per winning bidder. Defaults to 1.
+    /// Must be below `max_num_participants`, since the clearing price is the
+    /// highest losing bid.
+    pub quantity: Option<Uint64>,
+    /// If set, runs a combinatorial auction over these items, where bidders bid on
+    /// bundles with `ExecuteBundleBid`. See `combinatorial` for the limits.
+    pub items: Option<Vec<String>>,
+    /// Whether bids are committed as salted hashes and revealed later.
+    /// Defaults to open bidding.
+    pub sealed: Option<bool>,
+    /// The native denom or CW20 token bids must be paid in.
+    pub bid_denom: UncheckedDenom,
+    /// An address allowed to manage the auction alongside the contract owner.
+    pub auctioneer: Option<String>,
+    /// If set, only these addresses may bid.
+    /// The list may be updated while the auction is in progress.
+    pub allowed_bidders: Option<Vec<String>>,
+    /// When bidding opens. Defaults to immediately.
+    /// `start`, `end` and `reveal_end` must come in that order, and be either all
+    /// heights or all times.
+    pub start: Option<Scheduled>,
+    /// When bidding closes. Once it has passed anyone may close the auction,
+    /// or reveal their bid if the auction is sealed.
+    /// Without an end the auction runs until the owner or auctioneer closes it.
+    pub end: Option<Expiration>,
+    /// Sealed auctions only: when the reveal phase that follows `end` closes.
+    pub reveal_end: Option<Expiration>,
+    /// When bids may no longer be withdrawn, or lowered (asks raised in reverse
+    /// auctions). Defaults to allowing withdrawals for as long as bidding is open.
+    pub withdrawals_locked_after: Option<Expiration>,
+    /// Whether bidders may change the amount of their bid with `ExecuteUpdateBid`.
+    /// Defaults to true.
+    pub allow_amendments: Option<bool>,
+    /// What closing does with no more bids than items. Defaults to `Reject`.
+    /// Auctions with a reserve never need more bids than items.
+    pub on_insufficient_bids: Option<InsufficientBidsPolicy>,
+    /// The lowest price the items may be sold for, or in reverse auctions the highest
+    /// cost the buyer accepts. Winners settle at a price no better than the reserve,
+    /// and nothing is traded if no bid reaches it.
+    pub reserve: Option<ReservePrice>,
+    /// How bids of equal amount are ranked. Defaults to `LatestBid`.
+    pub tie_break: Option<TieBreakPolicy>,
+    /// Forward auctions only: who the clearing price is paid to, split by weight.
+    /// Defaults to the auction's creator.
+    pub beneficiaries: Option<Vec<ProceedsShare>>,
+    /// If set, every bid, bundle bid and sealed commitment must carry this bond in
+    /// `bid_denom`. Defaults to no bond.
+    pub bond: Option<BondTerms>,
+}
+
+#[cw_serde]
+pub struct ProceedsShare {
+    pub address: String,
+    pub weight: u32,
+}
+
+/// A bond deposited with each bid on top of its escrow. It is returned once the
+/// bidder withdraws, loses, or wins and settles, and slashed if they never reveal
+/// a sealed bid or default as a winner.
+#[cw_serde]
+pub struct BondTerms {
+    pub amount: Uint128,
+    /// The address slashed bonds are paid to.
+    pub slash_recipient: String,
+}
 
 #[cw_serde]
+pub enum TieBreakPolicy {
+    EarliestBid,
+    LatestBid,
+    LowestAddress,
+    /// A lottery drawn from a seed, committed to with `helpers::lottery_commitment`.
+    /// It must be revealed with `ExecuteRevealLotterySeed` before the auction closes,
+    /// unless the auction has passed its end, when ties go to the earliest bid.
+    Lottery(HexBinary),
+}
+
+/// The last bid of the previous page, to page through an auction's bids from.
+#[cw_serde]
+pub struct BidCursor {
+    pub amount: Uint128,
+    pub bidder: String,
+}
+
+#[cw_serde]
+pub enum ReservePrice {
+    /// A reserve bidders can see.
+    Public(Uint128),
+    /// A hidden reserve, see `helpers::reserve_commitment`. It must be revealed with
+    /// `ExecuteRevealReserve` before the auction closes, or nothing is sold.
+    Secret(HexBinary),
+}
+
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
-    ExecuteStartAuction {
-        /// The name of the auction.
-        name: String,
-        /// The number of participants in the auction.
-        /// Each participant may only bid once.
-        max_num_participants: Uint64,
-    },
+    /// Allows the owner to start an auction. Any number of auctions may be open at once,
+    /// and the new auction's id is returned in the `auction_id` attribute.
+    /// Coins attached to a forward auction are the lot it sells, held in escrow until the
+    /// winner receives them on settlement, or returned if the auction doesn't clear.
+    ExecuteStartAuction(StartAuctionMsg),
     /// Allows a participant to bid in the auction.
+    /// The bid amount must be attached in the auction's denom and is held in escrow.
+    /// Bids in reverse auctions are asks and are placed without funds.
+    /// Auctions with a bond take it on top of the funds.
     ExecuteBid {
+        auction_id: Uint64,
         /// The participant's bid.
         bid_amount: Uint128,
     },
-    /// Allows anyone to end the auction.
-    /// The auction may only be ended once the total number of bids has been received.
-    ExecuteCloseAuction {},
+    /// Allows a participant to bid on bundles of items in a combinatorial auction.
+    /// They win at most one bundle, and the most they offered for any bundle must
+    /// be attached in the auction's denom.
+    ExecuteBundleBid {
+        auction_id: Uint64,
+        bundles: Vec<BundleValue>,
+    },
+    /// Allows a participant to change the amount of their bid while bidding is open.
+    /// Raising a bid takes the difference attached in the auction's denom, and lowering
+    /// it refunds the difference. The bid is ranked as if it were placed now.
+    /// Not available in sealed or combinatorial auctions.
+    ExecuteUpdateBid {
+        auction_id: Uint64,
+        new_amount: Uint128,
+    },
+    /// Allows a participant to commit to a bid in a sealed auction.
+    /// A deposit of at least the bid must be attached in the auction's denom,
+    /// except in reverse auctions, plus the bond if the auction has one.
+    ExecuteCommitBid {
+        auction_id: Uint64,
+        /// The hash of the bid, see `helpers::bid_commitment`.
+        commitment: HexBinary,
+    },
+    /// Allows the owner or auctioneer to end the commit phase of a sealed auction
+    /// so bids can be revealed.
+    ExecuteStartReveal { auction_id: Uint64 },
+    /// Reveals a bid previously committed to a sealed auction.
+    ExecuteRevealBid {
+        auction_id: Uint64,
+        amount: Uint128,
+        salt: String,
+    },
+    /// Allows a participant to withdraw their bid, or their commitment in a sealed
+    /// auction, while bidding is open. Escrowed funds and the bond are refunded and
+    /// the bidder may bid again.
+    ExecuteWithdrawBid { auction_id: Uint64 },
+    /// Allows the owner or auctioneer to reveal the auction's secret reserve.
+    ExecuteRevealReserve {
+        auction_id: Uint64,
+        amount: Uint128,
+        salt: String,
+    },
+    /// Allows the owner or auctioneer to reveal the seed of the auction's tie-break lottery.
+    ExecuteRevealLotterySeed { auction_id: Uint64, seed: String },
+    /// Accepts bids in auctions denominated in a CW20 token.
+    /// The wrapped message must be a `ReceiveMsg`. Only tokens added with
+    /// `ExecuteUpdateTrustedContracts` may start auctions.
+    Receive(Cw20ReceiveMsg),
+    /// Starts an auction selling the CW721 token sent, which is held in escrow.
+    /// The winner receives it on settlement, and the sender gets it back if the
+    /// auction is cancelled or closes without a sale.
+    /// The wrapped message must be a `ReceiveNftMsg`, sent from a CW721 contract added
+    /// with `ExecuteUpdateTrustedContracts`.
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Allows the owner or auctioneer to change who may bid in an auction started
+    /// with `allowed_bidders`. Bids that were already placed are kept, but removed
+    /// bidders can no longer update them.
+    ExecuteUpdateAllowedBidders {
+        auction_id: Uint64,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Allows the owner to change which CW20 and CW721 contracts may start auctions
+    /// through `Receive` and `ReceiveNft`.
+    ExecuteUpdateTrustedContracts {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Allows the owner to propose a new owner, who must accept before the transfer happens.
+    ExecuteProposeNewOwner { new_owner: String },
+    /// Allows the owner to set or remove the protocol fee. Auctions keep the fee
+    /// in force when they started.
+    ExecuteUpdateFeeConfig { fee: Option<ProtocolFee> },
+    /// Allows the proposed owner to accept ownership.
+    ExecuteAcceptOwnership {},
+    /// Allows the owner to give up ownership, after which no auctions can be started.
+    ExecuteRenounceOwnership {},
+    /// Allows the owner or auctioneer to end the auction, or anyone once its schedule has run out.
+    /// The winners and the clearing price they owe are determined and stored on close.
+    /// Sealed auctions must be in their reveal phase, and unrevealed bids are ignored.
+    /// Losing bids are refunded and winners are refunded their bid minus the clearing price.
+    /// In reverse auctions the budget not owed to the winners goes back to the buyer.
+    /// Losers get their bond back, while the bonds of unrevealed sealed bids are slashed.
+    ExecuteCloseAuction { auction_id: Uint64 },
+    /// Allows anyone to pay out a closed auction's proceeds. Forward auctions pay what
+    /// the winners owe to the beneficiaries, split by weight with any rounding remainder
+    /// going to the first. Reverse auctions pay each winner out of the budget, and only
+    /// the buyer, owner or auctioneer may settle them. Winners get their bond back.
+    ExecuteSettleAuction { auction_id: Uint64 },
+    /// Allows the owner or auctioneer to declare that a winner of a closed, unsettled
+    /// reverse auction with a bond failed to deliver. The winner's bond is slashed,
+    /// they are dropped from the winners and what they were owed goes back to the buyer.
+    ExecuteDeclareDefault { auction_id: Uint64, bidder: String },
+    /// Allows the owner or auctioneer to abort an auction that has not closed.
+    /// No winners are determined, every escrowed bid and bond is refunded, and in reverse
+    /// auctions the budget goes back to the buyer. The reason is kept for queries.
+    ExecuteCancelAuction { auction_id: Uint64, reason: String },
+}
+
+/// Messages embedded in a CW20 `Send` to this contract.
+/// The tokens sent are the bid, the deposit for a sealed bid, the budget of a
+/// reverse auction, or the lot sold by a forward auction.
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
+pub enum ReceiveMsg {
+    ExecuteStartAuction(StartAuctionMsg),
+    ExecuteBid {
+        auction_id: Uint64,
+        bid_amount: Uint128,
+    },
+    ExecuteUpdateBid {
+        auction_id: Uint64,
+        new_amount: Uint128,
+    },
+    ExecuteCommitBid {
+        auction_id: Uint64,
+        commitment: HexBinary,
+    },
+    ExecuteBundleBid {
+        auction_id: Uint64,
+        bundles: Vec<BundleValue>,
+    },
+}
+
+/// Messages embedded in a CW721 `SendNft` to this contract.
+#[cw_serde]
+pub enum ReceiveNftMsg {
+    /// Must be sent by the contract owner. The auction must be a forward auction
+    /// of a single item, and not combinatorial.
+    ExecuteStartAuction(StartAuctionMsg),
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Required when migrating from 0.1.0, which had no owner: the address
+    /// allowed to start auctions.
+    pub owner: Option<String>,
+    /// Required when migrating from 0.1.0, which took bids without escrow:
+    /// the denom recorded on its auctions.
+    ///
+    /// 0.1.0 auctions keep the winner it would have reported. Closed ones are
+    /// marked settled and open ones are closed, and since no bid was escrowed,
+    /// settling them only records the outcome: nothing is paid out.
+    pub bid_denom: Option<UncheckedDenom>,
+}
+
+#[cw_serde]
+pub enum UncheckedDenom {
+    Native(String),
+    Cw20(String),
+}
+
+impl UncheckedDenom {
+    pub fn into_checked(self, deps: Deps) -> StdResult<Denom> {
+        Ok(match self {
+            UncheckedDenom::Native(denom) => Denom::Native(denom),
+            UncheckedDenom::Cw20(address) => Denom::Cw20(deps.api.addr_validate(&address)?),
+        })
+    }
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    #[returns(cosmwasm_std::Addr)]
+    /// Returns the first winner of a closed auction, see `QueryGetAuctionWinners`.
+    #[returns(QueryAuctionWinnerResponse)]
     QueryGetAuctionWinner { auction_id: Uint64 },
-    #[returns(Vec<Uint128>)]
+    /// Returns every winner of a closed auction and what they owe.
+    /// Fails with the cancellation reason if the auction was cancelled.
+    #[returns(QueryAuctionWinnersResponse)]
+    QueryGetAuctionWinners { auction_id: Uint64 },
+    /// Returns the bidder's bids in ascending order of auction id.
+    #[returns(QueryBidsForBidderResponse)]
     QueryGetBidsForBidder {
         bidder: String,
-        start_after: Option<Uint128>,
+        /// The auction id to start after.
+        start_after: Option<Uint64>,
         limit: Option<u32>,
     },
+    /// Returns the auction's bids in ascending order of amount, then of bidder.
     #[returns(Vec<Bid>)]
     QueryGetBidsForAuction {
         auction_id: Uint64,
-        start_after: Option<Uint128>,
+        start_after: Option<BidCursor>,
         limit: Option<u32>,
     },
-    #[returns(Uint64)]
+    /// Returns the id of the most recently started auction.
+    #[returns(QueryCurrentAuctionIdResponse)]
     QueryGetCurrentAuctionId {},
+    /// Returns the ids of auctions that have not been closed, in ascending order.
+    #[returns(QueryOpenAuctionsResponse)]
+    QueryGetOpenAuctions {
+        start_after: Option<Uint64>,
+        limit: Option<u32>,
+    },
+    #[returns(QueryOwnerResponse)]
+    QueryGetOwner {},
+    /// Returns the CW20 and CW721 contracts that may start auctions, in ascending order.
+    #[returns(QueryTrustedContractsResponse)]
+    QueryGetTrustedContracts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the protocol fee charged on auctions started from now on.
+    #[returns(QueryFeeConfigResponse)]
+    QueryGetFeeConfig {},
+    #[returns(QueryAuctionStatusResponse)]
+    QueryGetAuctionStatus { auction_id: Uint64 },
+    #[returns(QueryAllowedBiddersResponse)]
+    QueryGetAllowedBidders {
+        auction_id: Uint64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the bond each bidder deposited and whether it is held, returned or
+    /// slashed, in ascending order of bidder.
+    #[returns(QueryBondsResponse)]
+    QueryGetBonds {
+        auction_id: Uint64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -50,9 +374,91 @@ pub struct QueryCurrentAuctionIdResponse {
     pub auction_id: Uint64,
 }
 
+#[cw_serde]
+pub struct QueryOpenAuctionsResponse {
+    pub auction_ids: Vec<Uint64>,
+}
+
+#[cw_serde]
+pub struct QueryOwnerResponse {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct QueryTrustedContractsResponse {
+    pub contracts: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct QueryFeeConfigResponse {
+    pub fee: Option<FeeConfig>,
+}
+
+#[cw_serde]
+pub struct QueryAuctionStatusResponse {
+    pub auction_id: Uint64,
+    pub direction: AuctionDirection,
+    pub phase: AuctionPhase,
+    /// The blocks or seconds left in the current phase, if it has a deadline.
+    pub time_remaining: Option<Duration>,
+    pub start: Option<Scheduled>,
+    pub end: Option<Expiration>,
+    pub reveal_end: Option<Expiration>,
+    pub withdrawals_locked_after: Option<Expiration>,
+    /// The reserve price, unless it is secret and has not been revealed.
+    pub reserve: Option<Uint128>,
+    /// Why the auction was cancelled, if it was.
+    pub cancel_reason: Option<String>,
+    pub beneficiaries: Vec<Beneficiary>,
+    /// What the auction sells, if it escrowed an item.
+    pub lot: Option<Lot>,
+    /// The bond each bid must carry, if any.
+    pub bond: Option<BidBond>,
+}
+
+#[cw_serde]
+pub struct QueryAllowedBiddersResponse {
+    pub bidders: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct QueryBondsResponse {
+    pub bonds: Vec<BidderBond>,
+}
+
+#[cw_serde]
+pub struct BidderBond {
+    pub bidder: Addr,
+    pub amount: Uint128,
+    pub status: BondStatus,
+}
+
 #[cw_serde]
 pub struct QueryAuctionWinnerResponse {
-    pub winner: Winner,
+    /// `None` if the auction closed without a sale.
+    pub winner: Option<Winner>,
+    /// Whether the reserve raised the price or prevented a sale.
+    pub reserve_binding: bool,
+    /// How bids of equal amount were ranked, with the lottery seed if one was drawn.
+    pub tie_break: TieBreak,
+    /// The part of the winners' payments that goes to the fee collector on settlement.
+    /// Winners pay what they owe either way, the fee comes out of the proceeds.
+    pub protocol_fee: Uint128,
+}
+
+#[cw_serde]
+pub struct QueryAuctionWinnersResponse {
+    /// Ordered from the highest bid down, or by when they bid in combinatorial auctions.
+    /// Empty if the auction closed without a sale.
+    pub winners: Vec<Winner>,
+    /// Whether the reserve raised the price or prevented a sale.
+    pub reserve_binding: bool,
+    /// How bids of equal amount were ranked, with the lottery seed if one was drawn.
+    pub tie_break: TieBreak,
+    /// The part of the winners' payments that goes to the fee collector on settlement.
+    /// Winners pay what they owe either way, the fee comes out of the proceeds.
+    pub protocol_fee: Uint128,
 }
 
 #[cw_serde]
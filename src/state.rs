@@ -1,17 +1,174 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Storage, Timestamp, Uint128, Uint64};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{
+    Addr, BlockInfo, HexBinary, Order, StdResult, Storage, Timestamp, Uint128, Uint64,
+};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::{Duration, Expiration, Scheduled};
+use std::cmp::Ordering;
 
+use crate::combinatorial::{bundle_of, vcg_outcome};
+use crate::helpers::lottery_ticket;
 use crate::ContractError;
 
 #[cw_serde]
 pub struct Auction {
     pub id: u64,
     pub name: String,
-    pub in_progress: bool,
+    pub phase: AuctionPhase,
+    /// Whether the highest bids win (selling) or the lowest bids win (buying).
+    pub direction: AuctionDirection,
+    /// Reverse auctions only: the funds the buyer escrowed to pay the winners.
+    /// No bid may ask for more than `budget / quantity`.
+    pub budget: Uint128,
+    /// Sealed auctions take salted bid commitments while bidding and only
+    /// learn the amounts once bidders reveal them.
+    pub sealed: bool,
     pub max_participants: Uint64,
-    pub sorted_bids: Vec<Bid>,
-    pub winner: Option<Winner>,
+    /// The number of bids placed, or revealed in sealed auctions, stored in `BIDS`.
+    pub num_bids: u64,
+    pub num_commitments: u64,
+    /// The sequence number given to the next bid, bundle bid or commitment.
+    pub next_sequence: u64,
+    /// How bids of equal amount are ranked.
+    pub tie_break: TieBreak,
+    /// The number of identical items sold. Each of the `quantity` highest bidders
+    /// wins one and pays the next highest bid.
+    pub quantity: u64,
+    /// The items sold in a combinatorial auction, empty for single item auctions.
+    /// Bundles refer to items by their index in this list.
+    pub items: Vec<String>,
+    /// The native denom or CW20 token bids are escrowed in.
+    pub bid_denom: Denom,
+    pub creator: Addr,
+    /// An address allowed to manage this auction alongside the contract owner.
+    pub auctioneer: Option<Addr>,
+    /// Whether only addresses in `ALLOWED_BIDDERS` may bid.
+    pub restrict_bidders: bool,
+    /// What closing does when there are too few bids to set a clearing price.
+    pub insufficient_bids_policy: InsufficientBidsPolicy,
+    /// The lowest price the items may be sold for.
+    pub reserve: Option<Reserve>,
+    /// Set at close if the reserve raised the price or prevented a sale.
+    pub reserve_binding: bool,
+    /// When bidding opens. Bidding opens immediately if unset.
+    pub bidding_start: Option<Scheduled>,
+    /// When bidding closes. After this anyone may close the auction, or
+    /// reveal their bid for sealed auctions.
+    pub bidding_end: Option<Expiration>,
+    /// Sealed auctions only: when the reveal phase closes.
+    pub reveal_end: Option<Expiration>,
+    /// When bids may no longer be withdrawn. Bids may be withdrawn for as long
+    /// as bidding is open if unset.
+    pub withdrawals_locked_after: Option<Expiration>,
+    /// Whether bidders may change the amount of an open bid.
+    pub allow_amendments: bool,
+    /// Combinatorial auctions only: bids in the order they were placed.
+    pub bundle_bids: Vec<BundleBid>,
+    /// Set when the auction closes, empty if it closed without a sale.
+    pub winners: Vec<Winner>,
+    /// Set if the auction was cancelled: why it was cancelled.
+    pub cancel_reason: Option<String>,
+    /// Forward auctions only: who the clearing price is paid to on settlement,
+    /// split by weight. Reverse auctions pay their winners instead.
+    pub beneficiaries: Vec<Beneficiary>,
+    /// The protocol fee in force when the auction started.
+    pub fee: Option<FeeConfig>,
+    /// Set at close: the part of the winners' payments that goes to the fee collector.
+    pub protocol_fee: Uint128,
+    /// What the auction sells, held in escrow until the auction is settled or
+    /// returned to the creator. `None` if the auction only has a name.
+    pub lot: Option<Lot>,
+    /// The bond every bid must carry, if any, tracked per bidder in `BONDS`.
+    pub bond: Option<BidBond>,
+}
+
+#[cw_serde]
+pub enum AuctionDirection {
+    /// Items are sold: the highest bidders win and pay the highest losing bid.
+    Forward,
+    /// Items are bought: the lowest bidders win and are paid the lowest losing bid.
+    Reverse,
+}
+
+impl AuctionDirection {
+    /// Whether a bid of `a` beats a bid of `b`.
+    pub fn prefers(&self, a: Uint128, b: Uint128) -> bool {
+        match self {
+            AuctionDirection::Forward => a > b,
+            AuctionDirection::Reverse => a < b,
+        }
+    }
+}
+
+#[cw_serde]
+pub enum TieBreak {
+    /// The bid placed first wins.
+    EarliestBid,
+    /// The bid placed last wins.
+    LatestBid,
+    /// The bidder whose address sorts first wins.
+    LowestAddress,
+    /// The bid with the lowest `helpers::lottery_ticket` wins. The seed is
+    /// committed as `sha256(seed)`, see `helpers::lottery_commitment`, and
+    /// set once it has been revealed.
+    Lottery {
+        commitment: HexBinary,
+        seed: Option<String>,
+    },
+}
+
+impl TieBreak {
+    /// Orders two bids of equal amount by their bidder and sequence number,
+    /// the winning bid first.
+    pub fn order(&self, a: (&Addr, u64), b: (&Addr, u64)) -> Ordering {
+        match self {
+            TieBreak::EarliestBid => a.1.cmp(&b.1),
+            TieBreak::LatestBid => b.1.cmp(&a.1),
+            TieBreak::LowestAddress => a.cmp(&b),
+            TieBreak::Lottery {
+                seed: Some(seed), ..
+            } => lottery_ticket(seed, a.1).cmp(&lottery_ticket(seed, b.1)),
+            // Open auctions, and those closed after their end without a seed
+            TieBreak::Lottery { seed: None, .. } => a.1.cmp(&b.1),
+        }
+    }
+}
+
+#[cw_serde]
+pub enum InsufficientBidsPolicy {
+    /// Closing fails until the auction has more bids than items.
+    Reject,
+    /// The auction closes without a winner and every bid is refunded.
+    NoSale,
+}
+
+#[cw_serde]
+pub enum Reserve {
+    Public(Uint128),
+    /// Committed as `sha256(amount || salt)`, see `helpers::reserve_commitment`.
+    /// `amount` is set once it has been revealed.
+    Secret {
+        commitment: HexBinary,
+        amount: Option<Uint128>,
+    },
+}
+
+#[cw_serde]
+pub enum AuctionPhase {
+    /// Waiting for the scheduled start of bidding.
+    Pending,
+    /// Accepting bids, or bid commitments for sealed auctions.
+    Bidding,
+    /// Sealed auctions only: commitments are closed and may be revealed.
+    Reveal,
+    /// The schedule has run out and anyone may close the auction.
+    Ended,
+    Closed,
+    /// Closed, and the clearing price has been paid out.
+    Settled,
+    /// Aborted before closing. There are no winners and every bid was refunded.
+    Cancelled,
 }
 
 #[cw_serde]
@@ -20,89 +177,586 @@ pub struct Bid {
     pub amount: Uint128,
     pub bidder: Addr,
     pub timestamp: Timestamp,
+    /// Orders the auction's bids by when they were placed or last amended,
+    /// starting from 0. Revealed bids keep the sequence number of their commitment.
+    pub sequence: u64,
+    /// The funds held in escrow for this bid.
+    pub deposit: Uint128,
+    /// How many times the amount was changed with `ExecuteUpdateBid`.
+    pub amendments: u64,
+    /// When the bid was placed or last amended.
+    pub last_updated: Timestamp,
+}
+
+/// XOR bids on bundles of items: the bidder wins at most one of the bundles.
+#[cw_serde]
+pub struct BundleBid {
+    pub auction_id: u64,
+    pub bidder: Addr,
+    pub bundles: Vec<BundleValue>,
+    pub timestamp: Timestamp,
+    pub sequence: u64,
+    /// The funds held in escrow for this bid, the most the bidder offered for any bundle.
+    pub deposit: Uint128,
+}
+
+#[cw_serde]
+pub struct BundleValue {
+    /// Indices into the auction's items.
+    pub items: Vec<u32>,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Commitment {
+    pub auction_id: u64,
+    pub bidder: Addr,
+    /// `sha256(amount || bidder || salt)`, see `helpers::bid_commitment`.
+    pub commitment: HexBinary,
+    pub revealed: bool,
+    pub timestamp: Timestamp,
+    pub sequence: u64,
+    /// The funds held in escrow for this commitment, at least the hidden bid.
+    pub deposit: Uint128,
+}
+
+#[cw_serde]
+pub enum Lot {
+    /// A CW721 token, escrowed by sending it to the contract with `ReceiveNft`.
+    Nft { contract: Addr, token_id: String },
+    /// Native or CW20 tokens, escrowed by sending them along with `ExecuteStartAuction`.
+    Tokens { denom: Denom, amount: Uint128 },
+}
+
+#[cw_serde]
+pub struct FeeConfig {
+    /// The fee in basis points of each clearing payment.
+    pub bps: u16,
+    pub collector: Addr,
+}
+
+impl FeeConfig {
+    /// Returns the fee taken from a payment of `amount`, rounded down.
+    pub fn fee_on(&self, amount: Uint128) -> Uint128 {
+        amount.multiply_ratio(self.bps, 10_000u128)
+    }
+}
+
+#[cw_serde]
+pub struct BidBond {
+    /// The bond deposited with each bid, on top of the bid's own escrow.
+    pub amount: Uint128,
+    /// Where slashed bonds are paid.
+    pub slash_recipient: Addr,
+}
+
+/// A bidder's bond in an auction.
+#[cw_serde]
+pub struct Bond {
+    pub amount: Uint128,
+    pub status: BondStatus,
+}
+
+#[cw_serde]
+pub enum BondStatus {
+    /// Held in escrow: the bid is open, or it won and the auction has not settled.
+    Held,
+    /// Paid back to the bidder after they withdrew, lost, or won and settled.
+    Returned,
+    /// Paid to the auction's slash recipient because the bidder never revealed
+    /// their sealed bid, or defaulted as a winner.
+    Slashed,
+}
+
+#[cw_serde]
+pub struct Beneficiary {
+    pub address: Addr,
+    /// This beneficiary's share of the proceeds is `weight / total weight`.
+    pub weight: u32,
 }
 
 #[cw_serde]
 pub struct Winner {
     pub auction_id: u64,
+    /// What the winner pays, or in reverse auctions what they are paid.
     pub amount_owed: Uint128,
     pub bidder: Addr,
+    /// Combinatorial auctions only: the items won.
+    pub bundle: Option<Vec<u32>>,
 }
 
+pub const OWNER: Item<Addr> = Item::new("owner");
+
+/// The address ownership is being transferred to, until it accepts.
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+/// The protocol fee charged on auctions started from now on. Unset if there is none.
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+
 pub const AUCTIONS: Map<u64, Auction> = Map::new("auctions");
 
+/// The id of the most recently started auction.
 pub const CURRENT_AUCTION_ID: Item<u64> = Item::new("current_auction_id");
 
-pub const BIDDERS_TO_BIDS: Map<(&Addr, u64), Bid> = Map::new("bidders_to_bids");
+/// The ids of auctions that have been started and not yet closed.
+pub const OPEN_AUCTIONS: Map<u64, ()> = Map::new("open_auctions");
+
+pub struct BidIndexes<'a> {
+    /// Orders each auction's bids by amount, then by bidder.
+    pub amount: MultiIndex<'a, (u64, u128), Bid, (u64, Addr)>,
+    /// Orders each auction's bids by amount, then by sequence number, so ties
+    /// can be read in the order they were placed.
+    pub sequence: MultiIndex<'a, (u64, u128, u64), Bid, (u64, Addr)>,
+    /// Each bidder's bids across auctions, in ascending order of auction id.
+    pub bidder: MultiIndex<'a, Addr, Bid, (u64, Addr)>,
+}
+
+impl<'a> IndexList<Bid> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Bid>> + '_> {
+        let v: Vec<&dyn Index<Bid>> = vec![&self.amount, &self.sequence, &self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Bids in auctions that aren't combinatorial, keyed by auction id and bidder.
+/// Sealed bids are stored once they are revealed.
+pub fn bids<'a>() -> IndexedMap<'a, (u64, &'a Addr), Bid, BidIndexes<'a>> {
+    let indexes = BidIndexes {
+        amount: MultiIndex::new(
+            |_, bid| (bid.auction_id, bid.amount.u128()),
+            "bids",
+            "bids__amount",
+        ),
+        sequence: MultiIndex::new(
+            |_, bid| (bid.auction_id, bid.amount.u128(), bid.sequence),
+            "bids",
+            "bids__sequence",
+        ),
+        bidder: MultiIndex::new(|_, bid| bid.bidder.clone(), "bids", "bids__bidder"),
+    };
+    IndexedMap::new("bids", indexes)
+}
+
+pub const COMMITMENTS: Map<(u64, &Addr), Commitment> = Map::new("commitments");
+
+pub const ALLOWED_BIDDERS: Map<(u64, &Addr), ()> = Map::new("allowed_bidders");
+
+/// CW20 and CW721 contracts whose receive hooks may start auctions. The sender they
+/// report is trusted to be the account that sent the tokens.
+pub const TRUSTED_CONTRACTS: Map<&Addr, ()> = Map::new("trusted_contracts");
+/// The bonds bidders deposited, keyed by auction id and bidder. Kept once they
+/// are returned or slashed so their outcome can be queried.
+pub const BONDS: Map<(u64, &Addr), Bond> = Map::new("bonds");
 
 impl Auction {
     // O(1)
-    pub fn new(id: u64, max_participants: Uint64, name: String) -> Self {
+    pub fn new(
+        id: u64,
+        max_participants: Uint64,
+        name: String,
+        sealed: bool,
+        bid_denom: Denom,
+        creator: Addr,
+    ) -> Self {
         Auction {
             id,
-            in_progress: false,
+            phase: AuctionPhase::Closed,
+            direction: AuctionDirection::Forward,
+            budget: Uint128::zero(),
+            sealed,
             max_participants,
-            sorted_bids: Vec::new(),
-            winner: None,
+            num_bids: 0,
+            num_commitments: 0,
+            next_sequence: 0,
+            tie_break: TieBreak::LatestBid,
+            quantity: 1,
+            items: Vec::new(),
+            bid_denom,
+            auctioneer: None,
+            restrict_bidders: false,
+            insufficient_bids_policy: InsufficientBidsPolicy::Reject,
+            reserve: None,
+            reserve_binding: false,
+            bidding_start: None,
+            bidding_end: None,
+            reveal_end: None,
+            withdrawals_locked_after: None,
+            allow_amendments: true,
+            bundle_bids: Vec::new(),
+            winners: Vec::new(),
+            cancel_reason: None,
+            beneficiaries: vec![Beneficiary {
+                address: creator.clone(),
+                weight: 1,
+            }],
+            fee: None,
+            protocol_fee: Uint128::zero(),
+            lot: None,
+            bond: None,
+            creator,
             name,
         }
     }
 
     // O(1)
     pub fn start(&mut self) {
-        self.in_progress = true;
+        self.phase = AuctionPhase::Bidding;
+    }
+
+    // O(1)
+    pub fn start_reveal(&mut self) {
+        self.phase = AuctionPhase::Reveal;
     }
 
     // O(1)
     pub fn end(&mut self) {
-        self.in_progress = false;
+        self.phase = AuctionPhase::Closed;
+    }
+
+    // O(1)
+    /// Returns the protocol fee taken from what `winner` owes, or is owed in reverse auctions.
+    pub fn winner_fee(&self, winner: &Winner) -> Uint128 {
+        self.fee
+            .as_ref()
+            .map_or(Uint128::zero(), |fee| fee.fee_on(winner.amount_owed))
+    }
+
+    // O(1)
+    pub fn settle(&mut self) {
+        self.phase = AuctionPhase::Settled;
+    }
+
+    // O(1)
+    pub fn cancel(&mut self, reason: String) {
+        self.phase = AuctionPhase::Cancelled;
+        self.cancel_reason = Some(reason);
     }
 
     // O(1)
     pub fn is_in_progress(&self) -> bool {
-        self.in_progress
+        !matches!(
+            self.phase,
+            AuctionPhase::Closed | AuctionPhase::Settled | AuctionPhase::Cancelled
+        )
+    }
+
+    // O(1)
+    /// Returns the phase at `block`, following the auction's schedule from
+    /// the phase it was last moved to.
+    pub fn current_phase(&self, block: &BlockInfo) -> AuctionPhase {
+        let is_expired = |expiration: &Option<Expiration>| {
+            expiration
+                .as_ref()
+                .is_some_and(|expiration| expiration.is_expired(block))
+        };
+        let reveal_phase = || {
+            if is_expired(&self.reveal_end) {
+                AuctionPhase::Ended
+            } else {
+                AuctionPhase::Reveal
+            }
+        };
+
+        match self.phase {
+            AuctionPhase::Bidding => {
+                if let Some(start) = &self.bidding_start {
+                    if !start.is_triggered(block) {
+                        return AuctionPhase::Pending;
+                    }
+                }
+                if !is_expired(&self.bidding_end) {
+                    AuctionPhase::Bidding
+                } else if self.sealed {
+                    reveal_phase()
+                } else {
+                    AuctionPhase::Ended
+                }
+            }
+            AuctionPhase::Reveal => reveal_phase(),
+            _ => self.phase.clone(),
+        }
+    }
+
+    // O(1)
+    /// Returns how long is left until the current phase ends, if it has a deadline.
+    pub fn time_remaining(&self, block: &BlockInfo) -> Option<Duration> {
+        match self.current_phase(block) {
+            AuctionPhase::Pending => match self.bidding_start.as_ref()? {
+                Scheduled::AtHeight(height) => Some(Duration::Height(height - block.height)),
+                Scheduled::AtTime(time) => {
+                    Some(Duration::Time(time.seconds() - block.time.seconds()))
+                }
+            },
+            AuctionPhase::Bidding => time_until(self.bidding_end.as_ref()?, block),
+            AuctionPhase::Reveal => time_until(self.reveal_end.as_ref()?, block),
+            _ => None,
+        }
+    }
+
+    // O(1)
+    pub fn is_combinatorial(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    // O(1)
+    /// Returns the number of bidders taking part. In sealed auctions this counts
+    /// every commitment, revealed or not.
+    pub fn participant_count(&self) -> u64 {
+        if self.is_combinatorial() {
+            self.bundle_bids.len() as u64
+        } else if self.sealed {
+            self.num_commitments
+        } else {
+            self.num_bids
+        }
+    }
+
+    // O(1)
+    /// Returns the sequence number for a new bid or commitment.
+    pub fn take_sequence(&mut self) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        sequence
+    }
+
+    // O(1)
+    pub fn add_commitment(&mut self) -> Result<(), ContractError> {
+        if self.num_commitments >= self.max_participants.u64() {
+            return Err(ContractError::MaxParticipantsReached {
+                max_participants: self.max_participants,
+            });
+        }
+        self.num_commitments += 1;
+        Ok(())
     }
 
-    // O(log n) - average case
-    // O(n) - worst case
+    // O(1)
     pub fn add_bid(&mut self, storage: &mut dyn Storage, bid: Bid) -> Result<(), ContractError> {
-        if self.sorted_bids.len() >= self.max_participants.u64() as usize {
+        if self.num_bids >= self.max_participants.u64() {
             return Err(ContractError::MaxParticipantsReached {
                 max_participants: self.max_participants,
             });
         }
-        let index = match self
-            .sorted_bids
-            .binary_search_by_key(&bid.amount, |b| b.amount)
+        bids().save(storage, (self.id, &bid.bidder), &bid)?;
+        self.num_bids += 1;
+        AUCTIONS.save(storage, self.id, self)?;
+        Ok(())
+    }
+
+    // O(1)
+    /// Removes the bidder's bid, returning it.
+    pub fn remove_bid(
+        &mut self,
+        storage: &mut dyn Storage,
+        bidder: &Addr,
+    ) -> Result<Bid, ContractError> {
+        let bid = bids()
+            .may_load(storage, (self.id, bidder))?
+            .ok_or(ContractError::BidNotFound {})?;
+        bids().remove(storage, (self.id, bidder))?;
+        self.num_bids -= 1;
+        AUCTIONS.save(storage, self.id, self)?;
+        Ok(bid)
+    }
+
+    // O(n) reads, plus every bid tied with the last one when a revealed lottery breaks ties
+    /// Returns the `n` best bids for the auction's direction, from best to worst.
+    /// Equal bids are ranked by the auction's tie-break policy.
+    pub fn ranked_bids(&self, storage: &dyn Storage, n: usize) -> StdResult<Vec<Bid>> {
+        let order = match self.direction {
+            AuctionDirection::Forward => Order::Descending,
+            AuctionDirection::Reverse => Order::Ascending,
+        };
+
+        let mut ranked = bids()
+            .idx
+            .amount
+            .sub_prefix(self.id)
+            .range(storage, None, None, order)
+            .take(n)
+            .map(|item| item.map(|(_, bid)| bid))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        // The index orders the bids tied with the n-th best bid by bidder, so
+        // they are read again in the order of the tie-break policy
+        if let Some(boundary) = ranked
+            .last()
+            .map(|bid| bid.amount)
+            .filter(|_| ranked.len() == n)
         {
-            Ok(index) => index,
-            Err(index) => index,
+            ranked.retain(|bid| bid.amount != boundary);
+            let remaining = n - ranked.len();
+            let key = (self.id, boundary.u128());
+            let tied = match &self.tie_break {
+                TieBreak::EarliestBid | TieBreak::Lottery { seed: None, .. } => bids()
+                    .idx
+                    .sequence
+                    .sub_prefix(key)
+                    .range(storage, None, None, Order::Ascending),
+                TieBreak::LatestBid => bids().idx.sequence.sub_prefix(key).range(
+                    storage,
+                    None,
+                    None,
+                    Order::Descending,
+                ),
+                TieBreak::LowestAddress | TieBreak::Lottery { .. } => bids()
+                    .idx
+                    .amount
+                    .prefix(key)
+                    .range(storage, None, None, Order::Ascending),
+            };
+            // Lottery tickets aren't indexed, so a revealed lottery reads every tied bid
+            let limit = match &self.tie_break {
+                TieBreak::Lottery { seed: Some(_), .. } => usize::MAX,
+                _ => remaining,
+            };
+            for item in tied.take(limit) {
+                ranked.push(item?.1);
+            }
+        }
+
+        ranked.sort_by(|a, b| {
+            let by_amount = match self.direction {
+                AuctionDirection::Forward => b.amount.cmp(&a.amount),
+                AuctionDirection::Reverse => a.amount.cmp(&b.amount),
+            };
+            by_amount.then_with(|| {
+                self.tie_break
+                    .order((&a.bidder, a.sequence), (&b.bidder, b.sequence))
+            })
+        });
+        ranked.truncate(n);
+        Ok(ranked)
+    }
+
+    // O(1)
+    /// Returns the reserve price, if it is known: the lowest acceptable price in
+    /// forward auctions and the highest acceptable cost in reverse auctions.
+    /// A secret reserve that was never revealed is not cleared by any bid.
+    pub fn reserve_price(&self) -> Option<Uint128> {
+        let unrevealed = match self.direction {
+            AuctionDirection::Forward => Uint128::MAX,
+            AuctionDirection::Reverse => Uint128::zero(),
         };
-        self.sorted_bids.insert(index, bid);
-        AUCTIONS.save(storage, self.id, &self)?;
-        Ok(())
+        match self.reserve.as_ref()? {
+            Reserve::Public(amount) => Some(*amount),
+            Reserve::Secret { amount, .. } => Some(amount.unwrap_or(unrevealed)),
+        }
     }
 
-    // O(1) - average case
-    // O(n) - worst case
-    pub fn get_highest_bid(&self) -> Option<&Bid> {
-        self.sorted_bids
-            .iter()
-            .rev()
-            .max_by_key(|bid| (bid.amount, bid.timestamp))
+    // O(1)
+    /// Reverse auctions only: the most a single bid may ask for, so the budget
+    /// covers every winner.
+    pub fn max_ask(&self) -> Uint128 {
+        self.budget / Uint128::from(self.quantity)
     }
 
-    // O(1) - average case
-    // O(n) - worst case
-    pub fn get_second_highest_bid(&self) -> Option<&Bid> {
-        if self.sorted_bids.len() < 2 {
-            None
-        } else {
-            let highest_bid = self.get_highest_bid().unwrap();
-            self.sorted_bids
-                .iter()
-                .filter(|bid| bid != &highest_bid)
-                .max_by_key(|bid| (bid.amount, bid.timestamp))
+    // O(1)
+    /// The number of bids needed to determine a winner.
+    pub fn min_bids(&self) -> u64 {
+        self.quantity + 1
+    }
+
+    // O(n log n)
+    /// The `quantity` best bidders win one item each at the best losing bid:
+    /// the highest bidders pay the next highest bid in forward auctions, the
+    /// lowest bidders are paid the next lowest bid in reverse auctions.
+    /// Returns no winners until there are more bids than items.
+    ///
+    /// With a reserve, only bids at least as good as it win, at a price no
+    /// better than the reserve. The reserve stands in for missing bids, so a
+    /// single bid can win.
+    ///
+    /// Combinatorial auctions are decided by `combinatorial::vcg_outcome`.
+    pub fn determine_winners(&self, storage: &dyn Storage) -> StdResult<Vec<Winner>> {
+        if self.is_combinatorial() {
+            return Ok(self.determine_bundle_winners());
         }
+
+        let quantity = self.quantity as usize;
+        let ranked_bids = self.ranked_bids(storage, quantity + 1)?;
+        let clearing_price = match (ranked_bids.get(quantity), self.reserve_price()) {
+            (Some(bid), Some(reserve)) if self.direction.prefers(reserve, bid.amount) => reserve,
+            (Some(bid), _) => bid.amount,
+            (None, Some(reserve)) => reserve,
+            (None, None) => return Ok(vec![]),
+        };
+        // A reserve standing in for missing asks can't pay more than the budget
+        let clearing_price = match self.direction {
+            AuctionDirection::Forward => clearing_price,
+            AuctionDirection::Reverse => clearing_price.min(self.max_ask()),
+        };
+        Ok(ranked_bids
+            .into_iter()
+            .take(quantity)
+            .filter(|bid| !self.direction.prefers(clearing_price, bid.amount))
+            .map(|bid| Winner {
+                auction_id: self.id,
+                amount_owed: clearing_price,
+                bidder: bid.bidder,
+                bundle: None,
+            })
+            .collect())
+    }
+
+    // O(quantity) reads
+    /// Whether the reserve set a worse price than the best losing bid, or
+    /// stopped every bid from winning.
+    pub fn is_reserve_binding(&self, storage: &dyn Storage) -> StdResult<bool> {
+        let reserve = match self.reserve_price() {
+            Some(reserve) => reserve,
+            None => return Ok(false),
+        };
+        let ranked_bids = self.ranked_bids(storage, self.quantity as usize + 1)?;
+        Ok(!ranked_bids.is_empty()
+            && ranked_bids
+                .get(self.quantity as usize)
+                .is_none_or(|bid| self.direction.prefers(reserve, bid.amount)))
+    }
+
+    fn determine_bundle_winners(&self) -> Vec<Winner> {
+        if (self.bundle_bids.len() as u64) < self.min_bids() {
+            return vec![];
+        }
+
+        // Allocations of equal value go to the bidders ranked first
+        let mut ranked_bids: Vec<&BundleBid> = self.bundle_bids.iter().collect();
+        ranked_bids.sort_by(|a, b| {
+            self.tie_break
+                .order((&a.bidder, a.sequence), (&b.bidder, b.sequence))
+        });
+
+        let bids: Vec<Vec<_>> = ranked_bids
+            .iter()
+            .map(|bid| {
+                bid.bundles
+                    .iter()
+                    .map(|bundle| (bundle_of(&bundle.items), bundle.amount))
+                    .collect()
+            })
+            .collect();
+
+        let mut awards = vcg_outcome(&bids, self.items.len());
+        awards.sort_by_key(|award| ranked_bids[award.bidder].sequence);
+        awards
+            .into_iter()
+            .map(|award| {
+                let bid = ranked_bids[award.bidder];
+                Winner {
+                    auction_id: self.id,
+                    amount_owed: award.payment,
+                    bidder: bid.bidder.clone(),
+                    bundle: Some(bid.bundles[award.bundle].items.clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn time_until(expiration: &Expiration, block: &BlockInfo) -> Option<Duration> {
+    match expiration {
+        Expiration::AtHeight(height) => Some(Duration::Height(height - block.height)),
+        Expiration::AtTime(time) => Some(Duration::Time(time.seconds() - block.time.seconds())),
+        Expiration::Never {} => None,
     }
 }